@@ -77,6 +77,7 @@ where
     B: BufMut,
     T: PacketWriter<B>,
 {
+    #[allow(refining_impl_trait)]
     fn append<W>(self, w: W) -> CounterWriter<B, impl PacketWriter<B>>
     where
         W: PacketWriter<B>,
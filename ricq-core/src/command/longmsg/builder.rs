@@ -1,15 +1,21 @@
+use crate::command::multi_msg::MultiMsgTarget;
 use crate::pb;
 use prost::Message;
 
 impl super::super::super::Engine {
-    pub fn build_long_req(&self, dst_uin: i64, msg_content: Vec<u8>, msg_ukey: Vec<u8>) -> Vec<u8> {
+    pub fn build_long_req(
+        &self,
+        target: MultiMsgTarget,
+        msg_content: Vec<u8>,
+        msg_ukey: Vec<u8>,
+    ) -> Vec<u8> {
         pb::longmsg::LongReqBody {
             subcmd: 1,
             term_type: 5,
             platform_type: 9,
             msg_up_req: vec![pb::longmsg::LongMsgUpReq {
-                msg_type: 3, // group
-                dst_uin,
+                msg_type: target.msg_type(),
+                dst_uin: target.dst_uin(),
                 msg_id: 0,
                 msg_content,
                 store_type: 2,
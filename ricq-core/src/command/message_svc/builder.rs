@@ -1,6 +1,7 @@
 use prost::Message;
 
 use crate::command::common::PbToBytes;
+use crate::command::message_svc::MessageFragment;
 use crate::pb;
 use crate::protocol::packet::Packet;
 
@@ -107,6 +108,47 @@ impl super::super::super::Engine {
         seq: i32,
         ran: i32,
         time: i64,
+    ) -> Packet {
+        let rich_text = pb::msg::RichText {
+            elems,
+            ptt,
+            ..Default::default()
+        };
+        self.build_send_rich_text_packet(
+            routing_head,
+            rich_text,
+            seq,
+            ran,
+            time,
+            MessageFragment::default(),
+        )
+    }
+
+    // MessageSvc.PbSendMsg 分片消息
+    pub fn build_send_fragment_packet(
+        &self,
+        routing_head: pb::msg::routing_head::RoutingHead,
+        elems: Vec<pb::msg::Elem>,
+        seq: i32,
+        ran: i32,
+        time: i64,
+        fragment: MessageFragment,
+    ) -> Packet {
+        let rich_text = pb::msg::RichText {
+            elems,
+            ..Default::default()
+        };
+        self.build_send_rich_text_packet(routing_head, rich_text, seq, ran, time, fragment)
+    }
+
+    fn build_send_rich_text_packet(
+        &self,
+        routing_head: pb::msg::routing_head::RoutingHead,
+        rich_text: pb::msg::RichText,
+        seq: i32,
+        ran: i32,
+        time: i64,
+        fragment: MessageFragment,
     ) -> Packet {
         let sync_cookie = self.sync_cookie(time);
         let req = pb::msg::SendMessageRequest {
@@ -114,17 +156,13 @@ impl super::super::super::Engine {
                 routing_head: Some(routing_head),
             }),
             content_head: Some(pb::msg::ContentHead {
-                pkg_num: Some(1),
-                pkg_index: Some(0),
-                div_seq: Some(0),
+                pkg_num: Some(fragment.pkg_num),
+                pkg_index: Some(fragment.pkg_index),
+                div_seq: Some(fragment.div_seq),
                 ..Default::default()
             }),
             msg_body: Some(pb::msg::MessageBody {
-                rich_text: Some(pb::msg::RichText {
-                    elems,
-                    ptt,
-                    ..Default::default()
-                }),
+                rich_text: Some(rich_text),
                 ..Default::default()
             }),
             msg_seq: Some(seq),
@@ -153,6 +191,23 @@ impl super::super::super::Engine {
         self.uni_packet("MessageSvc.PbGetGroupMsg", req.to_bytes())
     }
 
+    // MessageSvc.PbGetOneDayRoamMsg
+    pub fn build_get_one_day_roam_msg_request(
+        &self,
+        peer_uin: i64,
+        last_msg_time: i64,
+        random: i64,
+        count: u32,
+    ) -> Packet {
+        let req = pb::msg::PbGetOneDayRoamMsgReq {
+            peer_uin: Some(peer_uin as u64),
+            last_msg_time: Some(last_msg_time as u64),
+            random: Some(random as u64),
+            read_cnt: Some(count),
+        };
+        self.uni_packet("MessageSvc.PbGetOneDayRoamMsg", req.to_bytes())
+    }
+
     pub fn build_friend_recall_packet(
         &self,
         uin: i64,
@@ -1,6 +1,7 @@
 use bytes::{Buf, Bytes};
 
-use crate::pb::msg::GetMessageResponse;
+use crate::command::online_push::GroupMessagePart;
+use crate::pb::msg::{GetGroupMsgResp, GetMessageResponse, PbGetOneDayRoamMsgResp};
 use crate::{jce, RQError, RQResult};
 use prost::Message;
 
@@ -57,4 +58,60 @@ impl crate::Engine {
                 .collect(),
         })
     }
+
+    // MessageSvc.PbGetOneDayRoamMsg
+    pub fn decode_get_one_day_roam_msg_response(
+        &self,
+        payload: Bytes,
+    ) -> RQResult<super::RoamMessageResponse> {
+        let resp = PbGetOneDayRoamMsgResp::decode(&*payload)?;
+        if resp.result() != 0 {
+            return Err(RQError::Decode(format!(
+                "get roam msg error: {} {}",
+                resp.result(),
+                resp.err_msg()
+            )));
+        }
+        Ok(super::RoamMessageResponse {
+            last_msg_time: resp.last_msg_time() as i64,
+            random: resp.random() as i64,
+            complete: resp.is_complete() == 1,
+            msgs: resp.msg,
+        })
+    }
+
+    // MessageSvc.PbGetGroupMsg
+    pub fn decode_get_group_msg_response(
+        &self,
+        payload: Bytes,
+    ) -> RQResult<super::GroupMessageHistory> {
+        let resp = GetGroupMsgResp::decode(&*payload)?;
+        if resp.result() != 0 {
+            return Err(RQError::Decode(format!(
+                "get group msg error: {} {}",
+                resp.result(),
+                resp.errmsg()
+            )));
+        }
+        let mut errors = Vec::new();
+        let begin_seq = resp.return_begin_seq() as i32;
+        let end_seq = resp.return_end_seq() as i32;
+        let parts = resp
+            .msg
+            .into_iter()
+            // 已撤回或系统消息
+            .filter(|m| m.head.as_ref().is_some_and(|h| h.from_uin() != 0))
+            .filter_map(|m| {
+                GroupMessagePart::try_from(m)
+                    .map_err(|e| errors.push(e))
+                    .ok()
+            })
+            .collect();
+        Ok(super::GroupMessageHistory {
+            begin_seq,
+            end_seq,
+            parts,
+            errors,
+        })
+    }
 }
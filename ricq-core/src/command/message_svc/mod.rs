@@ -1,4 +1,6 @@
+use crate::command::online_push::GroupMessagePart;
 use crate::pb;
+use crate::RQError;
 
 pub mod builder;
 pub mod decoder;
@@ -10,3 +12,39 @@ pub struct MessageSyncResponse {
     pub pub_account_cookie: Option<Vec<u8>>,
     pub msgs: Vec<pb::msg::Message>,
 }
+
+/// 分片消息中的位置，同一条消息的分片使用相同的 div_seq
+#[derive(Debug, Clone, Copy)]
+pub struct MessageFragment {
+    pub pkg_num: i32,
+    pub pkg_index: i32,
+    pub div_seq: i32,
+}
+
+impl Default for MessageFragment {
+    fn default() -> Self {
+        Self {
+            pkg_num: 1,
+            pkg_index: 0,
+            div_seq: 0,
+        }
+    }
+}
+
+pub struct RoamMessageResponse {
+    /// 下一页的时间
+    pub last_msg_time: i64,
+    /// 下一页的 random
+    pub random: i64,
+    /// 是否已经没有更早的消息
+    pub complete: bool,
+    pub msgs: Vec<pb::msg::Message>,
+}
+
+pub struct GroupMessageHistory {
+    pub begin_seq: i32,
+    pub end_seq: i32,
+    pub parts: Vec<GroupMessagePart>,
+    /// 无法解析而跳过的消息
+    pub errors: Vec<RQError>,
+}
@@ -1,5 +1,13 @@
-use bytes::Bytes;
+use std::collections::HashMap;
+use std::io::Read;
 
+use bytes::{Buf, Bytes};
+use flate2::read::GzDecoder;
+
+use crate::command::multi_msg::{ForwardMessage, ForwardNode, MessageNode};
+use crate::crypto::qqtea_decrypt;
+use crate::msg::elem::RQElem;
+use crate::msg::MessageChain;
 use crate::{pb, RQError, RQResult};
 use prost::Message;
 
@@ -23,4 +31,174 @@ impl super::super::super::Engine {
             .pop()
             .ok_or(RQError::EmptyField("multimsg_applyup_rsp"))
     }
+
+    /// 解析从 thumb_down_para 下载的数据
+    ///
+    /// 格式: 0x28 | head_len(u32) | body_len(u32) | head | tea(body, msg_key) | 0x29
+    pub fn decode_multi_msg_download(
+        &self,
+        mut payload: Bytes,
+        msg_key: &[u8],
+    ) -> RQResult<Vec<ForwardMessage>> {
+        if payload.len() < 9 || payload.get_u8() != 0x28 {
+            return Err(RQError::Decode("unexpected multi_msg body".into()));
+        }
+        if msg_key.len() != 16 {
+            return Err(RQError::Decode("invalid multi_msg msg_key".into()));
+        }
+        let head_len = payload.get_u32() as usize;
+        let body_len = payload.get_u32() as usize;
+        if payload.len() < head_len + body_len || !body_len.is_multiple_of(8) {
+            return Err(RQError::Decode("multi_msg body too short".into()));
+        }
+        payload.advance(head_len);
+        let body = qqtea_decrypt(&payload[..body_len], msg_key);
+        let content = pb::longmsg::LongRspBody::decode(&*body)?
+            .msg_down_rsp
+            .pop()
+            .ok_or(RQError::EmptyField("msg_down_rsp"))?
+            .msg_content;
+        let mut uncompressed = Vec::new();
+        GzDecoder::new(&*content).read_to_end(&mut uncompressed)?;
+        let trans = pb::msg::PbMultiMsgTransmit::decode(&*uncompressed)?;
+        Ok(parse_multi_msg_transmit(trans))
+    }
+}
+
+/// 将 PbMultiMsgTransmit 还原为转发消息，嵌套的转发消息通过 m_fileName 关联
+pub fn parse_multi_msg_transmit(trans: pb::msg::PbMultiMsgTransmit) -> Vec<ForwardMessage> {
+    let mut buffers: HashMap<String, Vec<pb::msg::Message>> = trans
+        .pb_item_list
+        .into_iter()
+        .filter_map(|item| Some((item.file_name?, item.buffer?.msg)))
+        .collect();
+    let msgs = if trans.msg.is_empty() {
+        buffers.remove("MultiMsg").unwrap_or_default()
+    } else {
+        buffers.remove("MultiMsg");
+        trans.msg
+    };
+    link_forward_messages(msgs, &mut buffers)
+}
+
+fn link_forward_messages(
+    msgs: Vec<pb::msg::Message>,
+    buffers: &mut HashMap<String, Vec<pb::msg::Message>>,
+) -> Vec<ForwardMessage> {
+    msgs.into_iter()
+        .map(|msg| {
+            let head = msg.head.unwrap_or_default();
+            let sender_name = match head.group_info {
+                Some(group_info) if head.msg_type == Some(82) => {
+                    String::from_utf8_lossy(group_info.group_card()).into_owned()
+                }
+                _ => head.from_nick.unwrap_or_default(),
+            };
+            let elements = MessageChain::from(
+                msg.body
+                    .and_then(|b| b.rich_text)
+                    .map(|r| r.elems)
+                    .unwrap_or_default(),
+            );
+            // 被移除后的 buffer 不会再被引用，避免循环嵌套
+            match forward_file_name(&elements).and_then(|name| buffers.remove(&name)) {
+                Some(nodes) => ForwardNode::new(
+                    head.from_uin.unwrap_or_default(),
+                    head.msg_time.unwrap_or_default(),
+                    sender_name,
+                    link_forward_messages(nodes, buffers),
+                )
+                .into(),
+                None => MessageNode {
+                    sender_id: head.from_uin.unwrap_or_default(),
+                    time: head.msg_time.unwrap_or_default(),
+                    sender_name,
+                    elements,
+                }
+                .into(),
+            }
+        })
+        .collect()
+}
+
+fn forward_file_name(elements: &MessageChain) -> Option<String> {
+    elements.clone().into_iter().find_map(|e| match e {
+        RQElem::RichMsg(rich) if rich.service_id == 35 => rich
+            .template1
+            .split_once("m_fileName=\"")
+            .and_then(|(_, v)| v.split_once('"'))
+            .map(|(v, _)| v.to_owned()),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BufMut;
+
+    use super::*;
+    use crate::command::multi_msg::MultiMsgTarget;
+    use crate::crypto::qqtea_encrypt;
+    use crate::msg::elem::Text;
+    use crate::protocol::device::Device;
+    use crate::protocol::version::ANDROID_PHONE;
+    use crate::Engine;
+
+    fn node(sender_id: i64, text: &str) -> ForwardMessage {
+        MessageNode {
+            sender_id,
+            time: 1,
+            sender_name: sender_id.to_string(),
+            elements: MessageChain::new(Text::new(text.into())),
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_decode_multi_msg_download() {
+        let engine = Engine::new(Device::random(), ANDROID_PHONE);
+        let data = engine.calculate_validation_data(
+            vec![
+                node(1, "hello"),
+                ForwardNode::new(2, 2, "2".into(), vec![node(3, "nested")]).into(),
+            ],
+            MultiMsgTarget::Group(12345),
+        );
+        let key = [7u8; 16];
+        let body = qqtea_encrypt(
+            &pb::longmsg::LongRspBody {
+                msg_down_rsp: vec![pb::longmsg::LongMsgDownRsp {
+                    msg_content: data,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }
+            .encode_to_vec(),
+            &key,
+        );
+        let mut payload = vec![0x28];
+        payload.put_u32(0);
+        payload.put_u32(body.len() as u32);
+        payload.extend(body);
+        payload.put_u8(0x29);
+
+        let msgs = engine
+            .decode_multi_msg_download(Bytes::from(payload), &key)
+            .unwrap();
+        assert_eq!(msgs.len(), 2);
+        match &msgs[0] {
+            ForwardMessage::Message(m) => {
+                assert_eq!(m.sender_id, 1);
+                assert_eq!(m.elements.to_string(), "hello ");
+            }
+            _ => panic!("expected message node"),
+        }
+        match &msgs[1] {
+            ForwardMessage::Forward(f) => {
+                assert_eq!(f.sender_id, 2);
+                assert!(matches!(&f.nodes[..], [ForwardMessage::Message(m)] if m.sender_id == 3));
+            }
+            _ => panic!("expected forward node"),
+        }
+    }
 }
@@ -1,31 +1,186 @@
 use std::collections::HashMap;
 use std::fmt::Write;
 
+use crate::common::{escape_xml, group_code2uin};
 use crate::msg::MessageChain;
 use crate::pb;
 
 pub mod builder;
 pub mod decoder;
 
+/// 转发消息、长消息的发送目标
+#[derive(Debug, Clone, Copy)]
+pub enum MultiMsgTarget {
+    Group(i64),
+    Friend(i64),
+}
+
+impl MultiMsgTarget {
+    pub fn dst_uin(&self) -> i64 {
+        match self {
+            MultiMsgTarget::Group(group_code) => group_code2uin(*group_code),
+            MultiMsgTarget::Friend(uin) => *uin,
+        }
+    }
+
+    /// MultiMsg.ApplyUp 和 LongConn 中的 msg_type
+    pub fn msg_type(&self) -> i32 {
+        match self {
+            MultiMsgTarget::Group(_) => 3,
+            MultiMsgTarget::Friend(_) => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum ForwardMessage {
     Message(MessageNode),
     Forward(ForwardNode),
 }
 
 pub fn gen_forward_preview(messages: &[ForwardMessage]) -> String {
+    gen_preview_lines(&default_preview_lines(messages))
+}
+
+fn default_preview_lines(messages: &[ForwardMessage]) -> Vec<String> {
+    messages
+        .iter()
+        .take(4)
+        .map(|msg| match msg {
+            ForwardMessage::Message(v) => format!("{}: {}", v.sender_name, v.elements.brief()),
+            ForwardMessage::Forward(v) => format!("{}: [转发消息]", v.sender_name),
+        })
+        .collect()
+}
+
+pub(super) fn gen_preview_lines(lines: &[String]) -> String {
     let mut ret = String::new();
-    for msg in messages.iter().take(4) {
-        ret.push_str(r##"<title size="26" color="#777777" maxLines="4" lineSpace="12">"##);
-        match msg {
-            ForwardMessage::Message(v) => write!(&mut ret, "{}: {}", v.sender_name, v.elements),
-            ForwardMessage::Forward(v) => write!(&mut ret, "{}: [转发消息]", v.sender_name),
-        }
+    for line in lines {
+        write!(
+            &mut ret,
+            r##"<title size="26" color="#777777" maxLines="4" lineSpace="12">{}</title>"##,
+            escape_xml(line)
+        )
         .unwrap();
-        ret.push_str("</title>");
     }
     ret
 }
 
+/// 转发消息、长消息卡片的外显内容，为 None 的字段使用默认值
+#[derive(Debug, Clone, Default)]
+pub struct ForwardPreview {
+    /// 标题，转发消息默认 `群聊的聊天记录`，长消息默认与 brief 相同
+    pub title: Option<String>,
+    /// 消息列表、通知栏中显示的文本，转发消息默认 `[聊天记录]`，长消息默认由内容生成
+    pub brief: Option<String>,
+    /// 底部摘要，转发消息默认 `查看{n}条转发消息`，长消息默认 `点击查看完整消息`
+    pub summary: Option<String>,
+    /// 来源，默认 `聊天记录`
+    pub source: Option<String>,
+    /// 预览行，转发消息默认为前 4 条消息的 `昵称: 内容`
+    pub preview: Option<Vec<String>>,
+}
+
+impl ForwardPreview {
+    /// 私聊转发消息的默认外显，标题为 `{a}和{b}的聊天记录`
+    pub fn private(a: &str, b: &str) -> Self {
+        Self {
+            title: Some(format!("{a}和{b}的聊天记录")),
+            ..Default::default()
+        }
+    }
+
+    /// 根据消息发送者生成私聊转发消息的外显，超过两人时与群聊相同
+    pub fn private_from_nodes(nodes: &[ForwardMessage]) -> Self {
+        let mut names: Vec<&str> = Vec::new();
+        for node in nodes {
+            let name = match node {
+                ForwardMessage::Message(m) => m.sender_name.as_str(),
+                ForwardMessage::Forward(f) => f.sender_name.as_str(),
+            };
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        match names[..] {
+            [a] => Self {
+                title: Some(format!("{a}的聊天记录")),
+                ..Default::default()
+            },
+            [a, b] => Self::private(a, b),
+            _ => Self::default(),
+        }
+    }
+
+    /// 生成转发消息卡片
+    pub fn forward_template(
+        &self,
+        res_id: &str,
+        file_name: &str,
+        nodes: &[ForwardMessage],
+    ) -> String {
+        let preview = match self.preview {
+            Some(ref lines) => gen_preview_lines(lines),
+            None => gen_forward_preview(nodes),
+        };
+        self.render_forward(res_id, file_name, nodes.len(), &preview)
+    }
+
+    pub(super) fn render_forward(
+        &self,
+        res_id: &str,
+        file_name: &str,
+        t_sum: usize,
+        preview: &str,
+    ) -> String {
+        format!(
+            r##"<?xml version='1.0' encoding='UTF-8' standalone='yes' ?><msg serviceID="35" templateID="1" action="viewMultiMsg" brief="{}" m_resid="{}" m_fileName="{}" tSum="{}" sourceMsgId="0" url="" flag="3" adverSign="0" multiMsgFlag="0"><item layout="1" advertiser_id="0" aid="0"><title size="34" maxLines="2" lineSpace="12">{}</title>{}<hr hidden="false" style="0" /><summary size="26" color="#777777">{}</summary></item><source name="{}" icon="" action="" appid="-1" /></msg>"##,
+            escape_xml(self.brief.as_deref().unwrap_or("[聊天记录]")),
+            escape_xml(res_id),
+            escape_xml(file_name),
+            t_sum,
+            escape_xml(self.title.as_deref().unwrap_or("群聊的聊天记录")),
+            preview,
+            escape_xml(
+                &self
+                    .summary
+                    .clone()
+                    .unwrap_or_else(|| format!("查看{t_sum}条转发消息"))
+            ),
+            escape_xml(self.source.as_deref().unwrap_or("聊天记录")),
+        )
+    }
+
+    /// 生成长消息卡片
+    pub fn long_message_template(
+        &self,
+        res_id: &str,
+        file_name: &str,
+        message_chain: &MessageChain,
+    ) -> String {
+        let brief = self
+            .brief
+            .clone()
+            .unwrap_or_else(|| message_chain.brief().chars().take(30).collect());
+        let preview = self
+            .preview
+            .as_deref()
+            .map(gen_preview_lines)
+            .unwrap_or_default();
+        format!(
+            r##"<?xml version='1.0' encoding='UTF-8' standalone='yes' ?><msg serviceID="35" templateID="1" action="viewMultiMsg" brief="{}" m_resid="{}" m_fileName="{}" sourceMsgId="0" url="" flag="3" adverSign="0" multiMsgFlag="1"><item layout="1"><title>{}</title>{}<hr hidden="false" style="0" /><summary>{}</summary></item><source name="{}" icon="" action="" appid="-1" /></msg>"##,
+            escape_xml(&brief),
+            escape_xml(res_id),
+            escape_xml(file_name),
+            escape_xml(self.title.as_deref().unwrap_or(&brief)),
+            preview,
+            escape_xml(self.summary.as_deref().unwrap_or("点击查看完整消息")),
+            escape_xml(self.source.as_deref().unwrap_or("聊天记录")),
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct MessageNode {
     pub sender_id: i64,
     pub time: i32,
@@ -39,11 +194,32 @@ impl From<MessageNode> for ForwardMessage {
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct ForwardNode {
     pub sender_id: i64,
     pub time: i32,
     pub sender_name: String,
     pub nodes: Vec<ForwardMessage>,
+    /// 嵌套转发消息卡片的外显，None 使用默认值
+    pub preview: Option<ForwardPreview>,
+}
+
+impl ForwardNode {
+    pub fn new(sender_id: i64, time: i32, sender_name: String, nodes: Vec<ForwardMessage>) -> Self {
+        Self {
+            sender_id,
+            time,
+            sender_name,
+            nodes,
+            preview: None,
+        }
+    }
+
+    /// 设置嵌套转发消息卡片的外显
+    pub fn with_preview(mut self, preview: ForwardPreview) -> Self {
+        self.preview = Some(preview);
+        self
+    }
 }
 
 impl From<ForwardNode> for ForwardMessage {
@@ -56,3 +232,74 @@ struct PackedMessage {
     pub filename: String,
     pub buffer: HashMap<String, Vec<pb::msg::Message>>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::elem::Text;
+
+    fn node(text: &str) -> ForwardMessage {
+        MessageNode {
+            sender_id: 1,
+            time: 0,
+            sender_name: "a<b".into(),
+            elements: MessageChain::new(Text::new(text.into())),
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_forward_template() {
+        let nodes = vec![node("x & y"), node("2"), node("3"), node("4"), node("5")];
+        let template = ForwardPreview::default().forward_template("res", "file", &nodes);
+        assert!(template.contains(r#"brief="[聊天记录]""#));
+        assert!(template.contains(r#"tSum="5""#));
+        assert!(template.contains(">群聊的聊天记录</title>"));
+        assert!(template.contains(">a&lt;b: x &amp; y</title>"));
+        assert!(!template.contains("a&lt;b: 5"));
+        assert!(template.contains(">查看5条转发消息</summary>"));
+
+        let template = ForwardPreview {
+            brief: Some("[日报]".into()),
+            summary: Some("共 5 条".into()),
+            source: Some("机器人".into()),
+            preview: Some(vec!["line".into()]),
+            ..ForwardPreview::private("甲", "乙")
+        }
+        .forward_template("res", "file", &nodes);
+        assert!(template.contains(r#"brief="[日报]""#));
+        assert!(template.contains(">甲和乙的聊天记录</title>"));
+        assert!(template.contains(r#"lineSpace="12">line</title><hr"#));
+        assert!(template.contains(">共 5 条</summary>"));
+        assert!(template.contains(r#"<source name="机器人""#));
+    }
+
+    #[test]
+    fn test_private_from_nodes() {
+        let named = |name: &str| -> ForwardMessage {
+            MessageNode {
+                sender_id: 1,
+                time: 0,
+                sender_name: name.into(),
+                elements: MessageChain::default(),
+            }
+            .into()
+        };
+        let title = |nodes: &[ForwardMessage]| ForwardPreview::private_from_nodes(nodes).title;
+        assert_eq!(title(&[named("甲")]).as_deref(), Some("甲的聊天记录"));
+        assert_eq!(
+            title(&[named("甲"), named("乙"), named("甲")]).as_deref(),
+            Some("甲和乙的聊天记录")
+        );
+        assert_eq!(title(&[named("甲"), named("乙"), named("丙")]), None);
+    }
+
+    #[test]
+    fn test_long_message_template() {
+        let chain = MessageChain::new(Text::new("hello".into()));
+        let template = ForwardPreview::default().long_message_template("res", "file", &chain);
+        assert!(template.contains(r#"brief="hello""#));
+        assert!(template.contains("<title>hello</title>"));
+        assert!(template.contains(r#"multiMsgFlag="1""#));
+    }
+}
@@ -0,0 +1,78 @@
+use serde_json::Value;
+
+/// 灰条提示，如 `<{"cmd":5,"data":"12345","text":"昵称"}>获得群主授予的<{"cmd":1,...,"text":"头衔"}>头衔`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GrayTip {
+    pub segments: Vec<GrayTipSegment>,
+    /// 片段替换为其 text 后的纯文本
+    pub text: String,
+}
+
+/// 灰条提示中的片段，cmd 5 一般为用户，data 为 uin
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GrayTipSegment {
+    pub cmd: i64,
+    pub data: String,
+    pub text: String,
+}
+
+impl GrayTip {
+    pub fn parse(content: &str) -> Self {
+        let mut tip = GrayTip::default();
+        let mut rest = content;
+        while let Some((before, after)) = rest.split_once("<{") {
+            tip.text.push_str(before);
+            let Some((json, after)) = after.split_once("}>") else {
+                tip.text.push_str("<{");
+                rest = after;
+                continue;
+            };
+            match serde_json::from_str::<Value>(&format!("{{{json}}}")) {
+                Ok(v) => {
+                    let field = |name: &str| match &v[name] {
+                        Value::String(s) => s.clone(),
+                        Value::Null => String::new(),
+                        v => v.to_string(),
+                    };
+                    let segment = GrayTipSegment {
+                        cmd: v["cmd"].as_i64().unwrap_or_default(),
+                        data: field("data"),
+                        text: field("text"),
+                    };
+                    tip.text.push_str(&segment.text);
+                    tip.segments.push(segment);
+                }
+                Err(_) => {
+                    tip.text.push_str("<{");
+                    tip.text.push_str(json);
+                    tip.text.push_str("}>");
+                }
+            }
+            rest = after;
+        }
+        tip.text.push_str(rest);
+        tip
+    }
+
+    /// 第一个 cmd 相同的片段
+    pub fn segment(&self, cmd: i64) -> Option<&GrayTipSegment> {
+        self.segments.iter().find(|s| s.cmd == cmd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let tip = GrayTip::parse(
+            r#"<{"cmd":5,"data":"12345","text":"小明"}>获得群主授予的<{"cmd":1,"data":"https://qun.qq.com/qqweb/m/qun/medal/detail.html?uin=12345","text":"龙王"}>头衔"#,
+        );
+        assert_eq!(tip.text, "小明获得群主授予的龙王头衔");
+        assert_eq!(tip.segments.len(), 2);
+        assert_eq!(tip.segment(5).unwrap().data, "12345");
+        assert_eq!(tip.segment(1).unwrap().text, "龙王");
+        assert_eq!(GrayTip::parse("a<{b").text, "a<{b");
+    }
+}
@@ -1,8 +1,13 @@
+use prost::Message;
+
 use crate::structs::{GroupDisband, GroupLeave, MemberPermissionChange};
-use crate::{jce, pb};
+use crate::{jce, pb, RQError};
 
 pub mod builder;
 pub mod decoder;
+mod gray_tip;
+
+pub use gray_tip::{GrayTip, GrayTipSegment};
 
 #[derive(Debug, Default)]
 pub struct ReqPush {
@@ -45,3 +50,77 @@ pub struct GroupMessagePart {
     // 分片id，相同id的应该合并，且根据pkg_index排序
     pub div_seq: i32,
 }
+
+impl TryFrom<pb::msg::Message> for GroupMessagePart {
+    type Error = RQError;
+
+    fn try_from(msg: pb::msg::Message) -> Result<Self, Self::Error> {
+        (|| {
+            let head = msg.head.ok_or("head")?;
+            let body = msg.body.ok_or("body")?;
+            // 历史消息中单条消息可能没有 content
+            let content = msg.content.unwrap_or(pb::msg::ContentHead {
+                pkg_num: Some(1),
+                ..Default::default()
+            });
+            let rich_text = body.rich_text.ok_or("rich_text")?;
+            let group_info = head.group_info.ok_or("group_info")?;
+            Ok(GroupMessagePart {
+                seq: head.msg_seq.ok_or("msg_seq")?,
+                rand: rich_text.attr.ok_or("attr")?.random.ok_or("attr.random")?,
+                group_code: group_info.group_code.ok_or("group_info.group_code")?,
+                group_name: String::from_utf8_lossy(&group_info.group_name.unwrap_or_default())
+                    .into_owned(),
+                group_card: decode_group_card(&group_info.group_card.unwrap_or_default()),
+                from_uin: head.from_uin.ok_or("from_uin")?,
+                elems: rich_text.elems,
+                time: head.msg_time.ok_or("msg_time")?,
+                pkg_num: content.pkg_num.ok_or("pkg_num")?,
+                pkg_index: content.pkg_index.unwrap_or_default(),
+                div_seq: content.div_seq.unwrap_or_default(),
+                ptt: rich_text.ptt,
+            })
+        })()
+        .map_err(|e: &'static str| RQError::Decode(format!("{e} is none")))
+    }
+}
+
+/// 解析消息中的群名片，0x0A 开头的是富文本名片 (D8FCCommCardNameBuf)，无法解析时为空
+pub fn decode_group_card(card: &[u8]) -> String {
+    if card.first() != Some(&0x0A) {
+        return String::from_utf8_lossy(card).into_owned();
+    }
+    pb::oidb::D8fcCommCardNameBuf::decode(card)
+        .map(|buf| {
+            buf.rich_card_name
+                .iter()
+                .map(|e| String::from_utf8_lossy(e.text()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_group_card() {
+        assert_eq!(decode_group_card("名片".as_bytes()), "名片");
+        let rich = pb::oidb::D8fcCommCardNameBuf {
+            rich_card_name: vec![
+                pb::oidb::D8fcRichCardNameElem {
+                    ctrl: None,
+                    text: Some("富文本".as_bytes().to_vec()),
+                },
+                pb::oidb::D8fcRichCardNameElem {
+                    ctrl: Some(vec![1]),
+                    text: Some("名片".as_bytes().to_vec()),
+                },
+            ],
+        }
+        .encode_to_vec();
+        assert_eq!(decode_group_card(&rich), "富文本名片");
+        assert_eq!(decode_group_card(&[0x0A, 0xFF]), "");
+    }
+}
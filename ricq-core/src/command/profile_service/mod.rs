@@ -6,6 +6,7 @@ pub mod builder;
 pub mod decoder;
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupSystemMessages {
     pub self_invited: Vec<SelfInvited>,
     pub join_group_requests: Vec<JoinGroupRequest>,
@@ -13,6 +14,7 @@ pub struct GroupSystemMessages {
 
 // 自己被邀请
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SelfInvited {
     pub msg_seq: i64,
     pub msg_time: i64,
@@ -26,6 +28,7 @@ pub struct SelfInvited {
 
 // 用户申请进群
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct JoinGroupRequest {
     pub msg_seq: i64,
     pub msg_time: i64,
@@ -41,11 +44,13 @@ pub struct JoinGroupRequest {
 }
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FriendSystemMessages {
     pub requests: Vec<NewFriendRequest>,
 }
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NewFriendRequest {
     pub msg_seq: i64,
     pub message: String,
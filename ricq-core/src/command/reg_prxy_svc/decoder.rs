@@ -1,7 +1,8 @@
 use bytes::{Buf, Bytes};
+use prost::Message;
 
 use crate::structs::OtherClientInfo;
-use crate::{jce, RQError, RQResult};
+use crate::{jce, pb, RQError, RQResult};
 
 impl super::super::super::Engine {
     // RegPrxySvc.PushParam
@@ -41,4 +42,81 @@ impl super::super::super::Engine {
             })
             .collect())
     }
+
+    // RegPrxySvc.getOffMsg
+    /// resp_PbOffMsg 与请求的 req_PbOffMsg 格式相同: 4 字节头 + GetMessageResponse
+    pub fn decode_get_offline_msg_response(
+        &self,
+        mut payload: Bytes,
+    ) -> RQResult<Vec<pb::msg::Message>> {
+        let mut request: jce::RequestPacket = jcers::from_buf(&mut payload)?;
+        let mut data: jce::RequestDataVersion3 = jcers::from_buf(&mut request.s_buffer)?;
+        let Some(mut buf) = data.map.remove("resp_PbOffMsg") else {
+            return Ok(Vec::new());
+        };
+        let mut body: Bytes = jcers::Jce::new(&mut buf).get_by_tag(0)?;
+        if body.len() < 4 {
+            return Ok(Vec::new());
+        }
+        body.advance(4);
+        let resp = pb::msg::GetMessageResponse::decode(body)?;
+        Ok(resp
+            .uin_pair_msgs
+            .into_iter()
+            .flat_map(|x| x.messages)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use bytes::BufMut;
+    use jcers::JcePut;
+
+    use super::*;
+    use crate::command::common::PbToBytes;
+    use crate::protocol::device::Device;
+    use crate::protocol::version::ANDROID_PHONE;
+    use crate::Engine;
+
+    #[test]
+    fn test_decode_get_offline_msg_response() {
+        let engine = Engine::new(Device::random(), ANDROID_PHONE);
+        let msg = pb::msg::Message {
+            head: Some(pb::msg::MessageHead {
+                from_uin: Some(10000),
+                msg_type: Some(166),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let resp = pb::msg::GetMessageResponse {
+            uin_pair_msgs: vec![pb::msg::UinPairMessage {
+                messages: vec![msg],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+        .to_bytes();
+        let mut body = vec![0, 0, 0, 0];
+        body.put_slice(&resp);
+        let mut pb_off_msg = jcers::JceMut::new();
+        pb_off_msg.put_bytes(Bytes::from(body), 0);
+        let data = jce::RequestDataVersion3 {
+            map: HashMap::from([("resp_PbOffMsg".to_string(), pb_off_msg.freeze())]),
+        };
+        let pkt = jce::RequestPacket {
+            i_version: 3,
+            s_servant_name: "RegPrxySvc".to_string(),
+            s_buffer: data.freeze(),
+            ..Default::default()
+        };
+        let msgs = engine
+            .decode_get_offline_msg_response(pkt.freeze())
+            .unwrap();
+        assert_eq!(msgs.len(), 1);
+        assert_eq!(msgs[0].head.as_ref().unwrap().from_uin(), 10000);
+    }
 }
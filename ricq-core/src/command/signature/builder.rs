@@ -18,7 +18,7 @@ impl super::super::super::Engine {
                 itemid: Some(0),
                 len: Some(signature.len() as i32 + 27),
                 data: Some({
-                    let mut buf = vec![0x3, signature.as_bytes().len() as u8 + 1, 0x20];
+                    let mut buf = vec![0x3, signature.len() as u8 + 1, 0x20];
                     buf.extend(signature.into_bytes());
                     buf.extend([
                         0x91, 0x04, 0x00, 0x00, 0x00, 0x00, 0x92, 0x04, 0x00, 0x00, 0x00, 0x00,
@@ -510,7 +510,7 @@ pub fn t511<B: BufMut + WriteLV>(domains: Vec<&str>) -> impl PacketWriter<B> + '
                         b |= 2
                     }
                     w.put_u8(b);
-                    w.write_short_lv(d[(index_of2 + 1) as usize..].as_bytes());
+                    w.write_short_lv(&d.as_bytes()[(index_of2 + 1) as usize..]);
                 }
             }
         }
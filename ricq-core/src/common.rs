@@ -60,12 +60,38 @@ impl From<RQAddr> for SocketAddr {
 
 impl From<SocketAddr> for RQAddr {
     fn from(addr: SocketAddr) -> Self {
-        let IpAddr::V4(ip) = addr.ip() else { panic!("is not ipv4") };
+        let IpAddr::V4(ip) = addr.ip() else {
+            panic!("is not ipv4")
+        };
         // ip.octets() returns little-endian
         Self(u32::from_le_bytes(ip.octets()), addr.port())
     }
 }
 
+pub(crate) fn escape_xml(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => ret.push_str("&amp;"),
+            '<' => ret.push_str("&lt;"),
+            '>' => ret.push_str("&gt;"),
+            '"' => ret.push_str("&quot;"),
+            '\'' => ret.push_str("&apos;"),
+            _ => ret.push(c),
+        }
+    }
+    ret
+}
+
+pub(crate) fn unescape_xml(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,6 +51,8 @@ pub enum RQError {
     SessionExpired,
     #[error("unsuccessful ret code: {0}")]
     UnsuccessfulRetCode(i32),
+    #[error("permission denied, {0}")]
+    PermissionDenied(&'static str),
 
     #[error("Token login failed")]
     TokenLoginFailed,
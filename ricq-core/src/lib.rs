@@ -1,6 +1,4 @@
 #![feature(impl_trait_in_assoc_type)]
-#![feature(type_alias_impl_trait)]
-#![feature(return_position_impl_trait_in_trait)]
 
 use std::sync::atomic::{AtomicI32, AtomicI64, AtomicU16, Ordering};
 
@@ -3,6 +3,7 @@ use crate::pb::msg;
 use crate::pb::msg::AnonymousGroupMessage;
 
 #[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Anonymous {
     // 用于禁言
     pub anon_id: Vec<u8>,
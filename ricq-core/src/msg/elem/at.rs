@@ -8,6 +8,7 @@ use crate::pb::msg;
 use crate::{push_builder_impl, to_elem_vec_impl};
 
 #[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct At {
     pub target: i64,
     pub display: String,
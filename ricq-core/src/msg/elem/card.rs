@@ -0,0 +1,473 @@
+use serde_json::{json, Value};
+
+use crate::common::{escape_xml, unescape_xml};
+use crate::msg::elem::{LightApp, RichMsg};
+use crate::msg::{MessageChainBuilder, PushBuilder};
+use crate::msg::{MessageElem, PushElem};
+use crate::{push_builder_impl, to_elem_vec_impl};
+
+/// 可识别的卡片消息，通过 [`RichMsg::card`] / [`LightApp::card`] 解析
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum Card {
+    Link(LinkCard),
+    FriendRecommend(FriendRecommendCard),
+    GroupRecommend(GroupRecommendCard),
+    Location(LocationCard),
+    MiniProgram(MiniProgramCard),
+}
+
+/// 链接分享，RichMsg service_id 1
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkCard {
+    pub url: String,
+    pub title: String,
+    pub summary: String,
+    /// 预览图网址
+    pub picture_url: String,
+    /// 从消息列表中看到的文字，为空时为 "[分享]" + title
+    pub brief: String,
+    /// 来源名称
+    pub source: String,
+}
+
+/// 推荐好友，RichMsg service_id 14
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FriendRecommendCard {
+    pub uin: i64,
+    pub nickname: String,
+}
+
+/// 推荐群聊，RichMsg service_id 15
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupRecommendCard {
+    pub group_code: i64,
+    pub group_name: String,
+    /// 卡片中的第二行文字，如群介绍
+    pub summary: String,
+}
+
+/// 位置分享，LightApp com.tencent.map
+///
+/// 发送时生成的 ark 没有签名，可能被服务器拒收
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LocationCard {
+    pub name: String,
+    pub address: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// 小程序，LightApp com.tencent.miniapp_01
+///
+/// 发送时生成的 ark 没有签名，可能被服务器拒收
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MiniProgramCard {
+    pub app_id: String,
+    /// 小程序名称
+    pub title: String,
+    pub desc: String,
+    pub icon_url: String,
+    pub preview_url: String,
+    pub url: String,
+}
+
+impl LinkCard {
+    pub fn new(url: String, title: String) -> Self {
+        Self {
+            url,
+            title,
+            ..Default::default()
+        }
+    }
+}
+
+impl FriendRecommendCard {
+    pub fn new(uin: i64, nickname: String) -> Self {
+        Self { uin, nickname }
+    }
+}
+
+impl GroupRecommendCard {
+    pub fn new(group_code: i64, group_name: String) -> Self {
+        Self {
+            group_code,
+            group_name,
+            ..Default::default()
+        }
+    }
+}
+
+impl LocationCard {
+    pub fn new(name: String, latitude: f64, longitude: f64) -> Self {
+        Self {
+            name,
+            latitude,
+            longitude,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<LinkCard> for RichMsg {
+    fn from(e: LinkCard) -> Self {
+        let brief = if e.brief.is_empty() {
+            format!("[分享]{}", e.title)
+        } else {
+            e.brief
+        };
+        RichMsg {
+            service_id: 1,
+            template1: format!(
+                r#"<?xml version='1.0' encoding='UTF-8' standalone='yes' ?><msg serviceID="1" templateID="1" action="web" brief="{}" sourceMsgId="0" url="{}" flag="0" adverSign="0" multiMsgFlag="0"><item layout="2" advertiser_id="0" aid="0"><picture cover="{}" w="0" h="0" /><title>{}</title><summary>{}</summary></item><source name="{}" icon="" action="" appid="-1" /></msg>"#,
+                escape_xml(&brief),
+                escape_xml(&e.url),
+                escape_xml(&e.picture_url),
+                escape_xml(&e.title),
+                escape_xml(&e.summary),
+                escape_xml(&e.source),
+            ),
+        }
+    }
+}
+
+impl From<FriendRecommendCard> for RichMsg {
+    fn from(e: FriendRecommendCard) -> Self {
+        let nickname = escape_xml(&e.nickname);
+        RichMsg {
+            service_id: 14,
+            template1: format!(
+                r#"<?xml version="1.0" encoding="utf-8"?><msg serviceID="14" templateID="1" action="plugin" actionData="AppCmd://OpenContactInfo/?uin={uin}" a_actionData="mqqapi://card/show_pslcard?src_type=internal&amp;source=sharecard&amp;version=1&amp;uin={uin}" i_actionData="mqqapi://card/show_pslcard?src_type=internal&amp;source=sharecard&amp;version=1&amp;uin={uin}" brief="推荐了{nickname}" m_resid="" m_fileName="{uin}" sourceMsgId="0" url="" flag="1" adverSign="0" multiMsgFlag="0"><item layout="0" mode="1" advertiser_id="0" aid="0"><summary>推荐联系人</summary><hr hidden="false" style="0" /></item><item layout="2" mode="1" advertiser_id="0" aid="0"><picture cover="https://q4.qlogo.cn/g?b=qq&amp;nk={uin}&amp;s=640" w="0" h="0" /><title>{nickname}</title><summary>帐号：{uin}</summary></item><source name="" icon="" action="" appid="-1" /></msg>"#,
+                uin = e.uin,
+            ),
+        }
+    }
+}
+
+impl From<GroupRecommendCard> for RichMsg {
+    fn from(e: GroupRecommendCard) -> Self {
+        let group_name = escape_xml(&e.group_name);
+        RichMsg {
+            service_id: 15,
+            template1: format!(
+                r#"<?xml version="1.0" encoding="utf-8"?><msg serviceID="15" templateID="1" action="web" actionData="group:{code}" a_actionData="group:{code}" i_actionData="group:{code}" brief="推荐群聊：{group_name}" m_resid="" m_fileName="{code}" sourceMsgId="0" url="https://qun.qq.com/qqweb/qunpro/share?inviteCode=&amp;from=181074&amp;biz=ka&amp;groupCode={code}" flag="0" adverSign="0" multiMsgFlag="0"><item layout="0" mode="1" advertiser_id="0" aid="0"><summary>推荐群聊</summary><hr hidden="false" style="0" /></item><item layout="2" mode="1" advertiser_id="0" aid="0"><picture cover="https://p.qlogo.cn/gh/{code}/{code}/100" w="0" h="0" needRoundView="0" /><title>{group_name}</title><summary>{summary}</summary></item><source name="" icon="" action="" appid="-1" /></msg>"#,
+                code = e.group_code,
+                summary = escape_xml(&e.summary),
+            ),
+        }
+    }
+}
+
+impl From<LocationCard> for LightApp {
+    fn from(e: LocationCard) -> Self {
+        LightApp::new(
+            json!({
+                "app": "com.tencent.map",
+                "desc": "地图",
+                "view": "LocationShare",
+                "ver": "0.0.0.1",
+                "prompt": format!("[位置]{}", e.name),
+                "meta": {
+                    "Location.Search": {
+                        "id": "",
+                        "name": e.name,
+                        "address": e.address,
+                        "lat": e.latitude.to_string(),
+                        "lng": e.longitude.to_string(),
+                        "from": "plusPanel",
+                    }
+                },
+                "config": {"forward": 1, "autosize": 1, "type": "card"},
+            })
+            .to_string(),
+        )
+    }
+}
+
+impl From<MiniProgramCard> for LightApp {
+    fn from(e: MiniProgramCard) -> Self {
+        LightApp::new(
+            json!({
+                "app": "com.tencent.miniapp_01",
+                "view": "view_8C8E89B49BE609866298ADDFF2DBABA4",
+                "ver": "1.0.0.19",
+                "prompt": format!("[QQ小程序]{}", e.title),
+                "meta": {
+                    "detail_1": {
+                        "appid": e.app_id,
+                        "title": e.title,
+                        "desc": e.desc,
+                        "icon": e.icon_url,
+                        "preview": e.preview_url,
+                        "url": e.url,
+                        "qqdocurl": e.url,
+                        "host": {"uin": 0, "nick": ""},
+                        "shareTemplateId": "8C8E89B49BE609866298ADDFF2DBABA4",
+                        "shareTemplateData": {},
+                    }
+                },
+                "config": {"type": "normal", "forward": 1, "autosize": 0},
+            })
+            .to_string(),
+        )
+    }
+}
+
+impl RichMsg {
+    /// 解析为可识别的卡片
+    pub fn card(&self) -> Option<Card> {
+        let xml = &self.template1;
+        match self.service_id {
+            1 if find_tag(xml, "title").is_some() => Some(Card::Link(LinkCard {
+                url: xml_attr(xml, "msg", "url"),
+                title: xml_element(xml, "title"),
+                summary: xml_element(xml, "summary"),
+                picture_url: xml_attr(xml, "picture", "cover"),
+                brief: xml_attr(xml, "msg", "brief"),
+                source: xml_attr(xml, "source", "name"),
+            })),
+            14 => Some(Card::FriendRecommend(FriendRecommendCard {
+                uin: xml_attr(xml, "msg", "m_fileName").parse().ok()?,
+                nickname: xml_element(xml, "title"),
+            })),
+            15 => Some(Card::GroupRecommend(GroupRecommendCard {
+                group_code: xml_attr(xml, "msg", "m_fileName").parse().ok()?,
+                group_name: xml_element(xml, "title"),
+                // 第一个 summary 是 "推荐群聊"
+                summary: xml
+                    .split_once("</title>")
+                    .map(|(_, s)| xml_element(s, "summary"))
+                    .unwrap_or_default(),
+            })),
+            _ => None,
+        }
+    }
+}
+
+impl LightApp {
+    /// 解析为可识别的卡片
+    pub fn card(&self) -> Option<Card> {
+        let content: Value = serde_json::from_str(&self.content).ok()?;
+        let str = |v: &Value| v.as_str().unwrap_or_default().to_owned();
+        match content["app"].as_str()? {
+            "com.tencent.structmsg" => {
+                let news = content["meta"].as_object()?.values().next()?;
+                Some(Card::Link(LinkCard {
+                    url: str(&news["jumpUrl"]),
+                    title: str(&news["title"]),
+                    summary: str(&news["desc"]),
+                    picture_url: str(&news["preview"]),
+                    brief: str(&content["prompt"]),
+                    source: str(&news["tag"]),
+                }))
+            }
+            "com.tencent.map" => {
+                let location = &content["meta"]["Location.Search"];
+                let float = |v: &Value| match v {
+                    Value::String(s) => s.parse().ok(),
+                    v => v.as_f64(),
+                };
+                Some(Card::Location(LocationCard {
+                    name: str(&location["name"]),
+                    address: str(&location["address"]),
+                    latitude: float(&location["lat"])?,
+                    longitude: float(&location["lng"])?,
+                }))
+            }
+            "com.tencent.miniapp_01" => {
+                let detail = &content["meta"]["detail_1"];
+                let url = match detail["qqdocurl"].as_str() {
+                    Some(url) if !url.is_empty() => url.to_owned(),
+                    _ => str(&detail["url"]),
+                };
+                Some(Card::MiniProgram(MiniProgramCard {
+                    app_id: str(&detail["appid"]),
+                    title: str(&detail["title"]),
+                    desc: str(&detail["desc"]),
+                    icon_url: str(&detail["icon"]),
+                    preview_url: str(&detail["preview"]),
+                    url,
+                }))
+            }
+            _ => None,
+        }
+    }
+}
+
+// 找到第一个 `<tag ...>`，返回 (属性部分, 标签之后的内容)
+fn find_tag<'a>(xml: &'a str, tag: &str) -> Option<(&'a str, &'a str)> {
+    let open = format!("<{tag}");
+    let mut rest = xml;
+    while let Some(i) = rest.find(&open) {
+        rest = &rest[i + open.len()..];
+        if rest.starts_with([' ', '\t', '\r', '\n', '>', '/']) {
+            return rest.split_once('>');
+        }
+    }
+    None
+}
+
+// 取第一个 `<tag>` 的 `name="..."` 属性
+fn xml_attr(xml: &str, tag: &str, name: &str) -> String {
+    find_tag(xml, tag)
+        .and_then(|(attrs, _)| attrs.split_once(&format!(" {name}=\"")))
+        .and_then(|(_, v)| v.split_once('"'))
+        .map(|(v, _)| unescape_xml(v))
+        .unwrap_or_default()
+}
+
+// 取第一个 `<name ...>...</name>` 元素的内容
+fn xml_element(xml: &str, name: &str) -> String {
+    find_tag(xml, name)
+        .filter(|(attrs, _)| !attrs.ends_with('/'))
+        .and_then(|(_, v)| v.split_once(&format!("</{name}>")))
+        .map(|(v, _)| unescape_xml(v))
+        .unwrap_or_default()
+}
+
+impl PushElem for Card {
+    fn push_to(elem: Self, vec: &mut Vec<MessageElem>) {
+        match elem {
+            Card::Link(e) => PushElem::push_to(e, vec),
+            Card::FriendRecommend(e) => PushElem::push_to(e, vec),
+            Card::GroupRecommend(e) => PushElem::push_to(e, vec),
+            Card::Location(e) => PushElem::push_to(e, vec),
+            Card::MiniProgram(e) => PushElem::push_to(e, vec),
+        }
+    }
+}
+
+macro_rules! impl_push_card {
+    ($t:ty, $elem:ty) => {
+        impl PushElem for $t {
+            fn push_to(elem: Self, vec: &mut Vec<MessageElem>) {
+                <$elem>::push_to(<$elem>::from(elem), vec);
+            }
+        }
+        to_elem_vec_impl!($t);
+        push_builder_impl!($t);
+    };
+}
+
+impl_push_card!(LinkCard, RichMsg);
+impl_push_card!(FriendRecommendCard, RichMsg);
+impl_push_card!(GroupRecommendCard, RichMsg);
+impl_push_card!(LocationCard, LightApp);
+impl_push_card!(MiniProgramCard, LightApp);
+to_elem_vec_impl!(Card);
+push_builder_impl!(Card);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_card() {
+        let card = LinkCard {
+            summary: "a <b> & c".into(),
+            picture_url: "https://example.com/a.png".into(),
+            source: "来源".into(),
+            ..LinkCard::new("https://example.com/?a=1&b=2".into(), "标题\"".into())
+        };
+        let rich_msg = RichMsg::from(card);
+        assert_eq!(rich_msg.service_id, 1);
+        assert!(rich_msg
+            .template1
+            .contains(r#"url="https://example.com/?a=1&amp;b=2""#));
+        let Some(Card::Link(parsed)) = rich_msg.card() else {
+            panic!("not a link card")
+        };
+        assert_eq!(parsed.url, "https://example.com/?a=1&b=2");
+        assert_eq!(parsed.title, "标题\"");
+        assert_eq!(parsed.summary, "a <b> & c");
+        assert_eq!(parsed.picture_url, "https://example.com/a.png");
+        assert_eq!(parsed.brief, "[分享]标题\"");
+        assert_eq!(parsed.source, "来源");
+    }
+
+    #[test]
+    fn test_link_card_with_attrs() {
+        let rich_msg = RichMsg {
+            service_id: 1,
+            template1: r##"<?xml version='1.0' encoding='UTF-8' standalone='yes' ?><msg serviceID="1" templateID="1" action="web" brief="[分享]标题" url="https://example.com"><item layout="2"><picture cover="https://example.com/a.png" w="0" h="0" /><title size="34" color="#000000">标题</title><summary size="26">描述</summary></item><source name="来源" icon="" /></msg>"##.into(),
+        };
+        let Some(Card::Link(parsed)) = rich_msg.card() else {
+            panic!("not a link card")
+        };
+        assert_eq!(parsed.url, "https://example.com");
+        assert_eq!(parsed.title, "标题");
+        assert_eq!(parsed.summary, "描述");
+        assert_eq!(parsed.picture_url, "https://example.com/a.png");
+        assert_eq!(parsed.source, "来源");
+        assert!(xml_element("<titles>a</titles><title/>", "title").is_empty());
+    }
+
+    #[test]
+    fn test_recommend_card() {
+        let rich_msg = RichMsg::from(FriendRecommendCard::new(12345, "<nick>".into()));
+        assert_eq!(rich_msg.service_id, 14);
+        let Some(Card::FriendRecommend(parsed)) = rich_msg.card() else {
+            panic!("not a friend recommend card")
+        };
+        assert_eq!(parsed.uin, 12345);
+        assert_eq!(parsed.nickname, "<nick>");
+
+        let rich_msg = RichMsg::from(GroupRecommendCard {
+            summary: "群介绍".into(),
+            ..GroupRecommendCard::new(335783090, "群名".into())
+        });
+        assert_eq!(rich_msg.service_id, 15);
+        let Some(Card::GroupRecommend(parsed)) = rich_msg.card() else {
+            panic!("not a group recommend card")
+        };
+        assert_eq!(parsed.group_code, 335783090);
+        assert_eq!(parsed.group_name, "群名");
+        assert_eq!(parsed.summary, "群介绍");
+    }
+
+    #[test]
+    fn test_light_app_card() {
+        let mut location = LocationCard::new("天安门".into(), 39.908823, 116.39747);
+        location.address = "北京市东城区".into();
+        let Some(Card::Location(parsed)) = LightApp::from(location).card() else {
+            panic!("not a location card")
+        };
+        assert_eq!(parsed.name, "天安门");
+        assert_eq!(parsed.address, "北京市东城区");
+        assert_eq!(parsed.latitude, 39.908823);
+        assert_eq!(parsed.longitude, 116.39747);
+
+        let mini_program = MiniProgramCard {
+            app_id: "1109937557".into(),
+            title: "哔哩哔哩".into(),
+            desc: "视频".into(),
+            url: "https://b23.tv/xxx".into(),
+            ..Default::default()
+        };
+        let Some(Card::MiniProgram(parsed)) = LightApp::from(mini_program).card() else {
+            panic!("not a mini program card")
+        };
+        assert_eq!(parsed.app_id, "1109937557");
+        assert_eq!(parsed.title, "哔哩哔哩");
+        assert_eq!(parsed.desc, "视频");
+        assert_eq!(parsed.url, "https://b23.tv/xxx");
+
+        let struct_msg = LightApp::new(
+            r#"{"app":"com.tencent.structmsg","view":"news","prompt":"[分享]标题","meta":{"news":{"title":"标题","desc":"描述","jumpUrl":"https://example.com","preview":"https://example.com/a.png","tag":"哔哩哔哩"}}}"#.into(),
+        );
+        let Some(Card::Link(parsed)) = struct_msg.card() else {
+            panic!("not a link card")
+        };
+        assert_eq!(parsed.url, "https://example.com");
+        assert_eq!(parsed.summary, "描述");
+        assert_eq!(parsed.source, "哔哩哔哩");
+
+        assert!(LightApp::new("{}".into()).card().is_none());
+    }
+}
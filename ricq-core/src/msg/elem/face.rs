@@ -8,9 +8,24 @@ use crate::pb::msg;
 use crate::{push_builder_impl, to_elem_vec_impl};
 
 #[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Face {
     pub index: i32,
     pub name: String,
+    /// 超级表情（大表情）的资源信息，普通表情为 None
+    pub sticker: Option<FaceSticker>,
+}
+
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaceSticker {
+    pub pack_id: String,
+    pub sticker_id: String,
+    /// 1 超级表情（lottie 动画），2 随机结果表情（骰子、包剪锤）
+    pub sticker_type: u32,
+    pub source_type: u32,
+    /// 随机结果表情的结果
+    pub result_id: String,
 }
 
 impl Face {
@@ -18,6 +33,21 @@ impl Face {
         Self {
             index: id,
             name: Self::name(id).into(),
+            sticker: None,
+        }
+    }
+
+    /// 超级表情，`sticker_id` 为表情在表情包中的资源 id
+    pub fn new_super(id: i32, sticker_id: String) -> Self {
+        Self {
+            sticker: Some(FaceSticker {
+                pack_id: "1".into(),
+                sticker_id,
+                sticker_type: 1,
+                source_type: 1,
+                result_id: String::new(),
+            }),
+            ..Self::new(id)
         }
     }
 
@@ -32,6 +62,30 @@ impl Face {
 
 impl PushElem for Face {
     fn push_to(e: Self, vec: &mut Vec<MessageElem>) {
+        if let Some(sticker) = e.sticker {
+            let elem = msg::MsgElemInfoServtype37 {
+                packid: Some(sticker.pack_id.into_bytes()),
+                stickerid: Some(sticker.sticker_id.into_bytes()),
+                qsid: Some(e.index as u32),
+                sourcetype: Some(sticker.source_type),
+                stickertype: Some(sticker.sticker_type),
+                resultid: Some(sticker.result_id.into_bytes()),
+                text: Some(format!("/{}", e.name).into_bytes()),
+                surpriseid: Some(Vec::new()),
+                randomtype: Some(1),
+            }
+            .encode_to_vec();
+            vec.push(msg::elem::Elem::CommonElem(msg::CommonElem {
+                service_type: Some(37),
+                pb_elem: Some(elem),
+                business_type: Some(sticker.sticker_type as i32),
+            }));
+            vec.push(msg::elem::Elem::Text(msg::Text {
+                str: Some(format!("[{}]请使用最新版手机QQ体验新功能", e.name)),
+                ..Default::default()
+            }));
+            return;
+        }
         let elem = if e.index >= 260 {
             let text = format!("/{}", e.name).as_bytes().to_vec();
             let elem = msg::MsgElemInfoServtype33 {
@@ -69,6 +123,27 @@ impl From<msg::MsgElemInfoServtype33> for Face {
     }
 }
 
+impl From<msg::MsgElemInfoServtype37> for Face {
+    fn from(e: msg::MsgElemInfoServtype37) -> Self {
+        let mut face = Self::new(e.qsid() as i32);
+        // 表中没有的表情使用消息中的名称
+        if face_id_map(face.index).is_none() {
+            if let Some(name) = std::str::from_utf8(e.text()).ok().filter(|s| !s.is_empty()) {
+                face.name = name.trim_start_matches('/').into();
+            }
+        }
+        let bytes_to_string = |b: &[u8]| String::from_utf8_lossy(b).into_owned();
+        face.sticker = Some(FaceSticker {
+            pack_id: bytes_to_string(e.packid()),
+            sticker_id: bytes_to_string(e.stickerid()),
+            sticker_type: e.stickertype(),
+            source_type: e.sourcetype(),
+            result_id: bytes_to_string(e.resultid()),
+        });
+        face
+    }
+}
+
 impl fmt::Display for Face {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "[{}]", self.name)
@@ -87,6 +162,33 @@ mod tests {
         let name = Face::name(1);
         println!("{name:?}")
     }
+
+    #[test]
+    fn test_face_map() {
+        assert_eq!(face_name_map("亲亲"), Some(360));
+        assert_eq!(face_name_map("狗狗笑哭"), Some(361));
+        assert_eq!(face_name_map("太沧桑"), Some(391));
+        assert_eq!(face_name_map("拜托"), Some(200));
+        assert_eq!(face_id_map(353), Some("拜托"));
+        assert!((332..=395).all(|id| id == 335 || face_id_map(id).is_some()));
+    }
+
+    #[test]
+    fn test_super_face() {
+        let mut vec = Vec::new();
+        Face::push_to(Face::new_super(319, "9".into()), &mut vec);
+        assert_eq!(vec.len(), 2);
+        let MessageElem::CommonElem(ref e) = vec[0] else {
+            panic!("not a common elem")
+        };
+        assert_eq!(e.service_type(), 37);
+        let face = Face::from(msg::MsgElemInfoServtype37::decode(e.pb_elem()).unwrap());
+        assert_eq!(face.index, 319);
+        assert_eq!(face.name, "比心");
+        let sticker = face.sticker.unwrap();
+        assert_eq!(sticker.sticker_id, "9");
+        assert_eq!(sticker.sticker_type, 1);
+    }
 }
 
 // pub fn face_id_map(key: i32) -> Option<&'static str> {
@@ -113,6 +215,8 @@ macro_rules! faces_map {
                 _ => None,
             }
         }
+        // 同名的表情取先出现的 id
+        #[allow(unreachable_patterns)]
         pub fn face_name_map(name: &str) -> Option<i32> {
             match name {
                 $(
@@ -383,5 +487,68 @@ faces_map!(
     (134, "右太极"),
     (133, "左太极"),
     (131, "街舞"),
-    (276, "辣椒酱")
+    (276, "辣椒酱"),
+    (332, "举牌牌"),
+    (333, "烟花"),
+    (334, "虎虎生威"),
+    (336, "豹富"),
+    (337, "花朵脸"),
+    (338, "我想开了"),
+    (339, "舔屏"),
+    (340, "热化了"),
+    (341, "打招呼"),
+    (342, "酸Q"),
+    (343, "我方了"),
+    (344, "大怨种"),
+    (345, "红包多多"),
+    (346, "你真棒棒"),
+    (347, "大展宏兔"),
+    (348, "福萝卜"),
+    (349, "坚强"),
+    (350, "贴贴"),
+    (351, "敲敲"),
+    (352, "咦"),
+    (353, "拜托"),
+    (354, "尊嘟假嘟"),
+    (355, "耶"),
+    (356, "666"),
+    (357, "裂开"),
+    (358, "骰子"),
+    (359, "包剪锤"),
+    (360, "亲亲"),
+    (361, "狗狗笑哭"),
+    (362, "好兄弟"),
+    (363, "狗狗可怜"),
+    (364, "超级赞"),
+    (365, "狗狗生气"),
+    (366, "芒狗"),
+    (367, "狗狗疑问"),
+    (368, "奥特笑哭"),
+    (369, "彩虹"),
+    (370, "祝贺"),
+    (371, "冒泡"),
+    (372, "气呼呼"),
+    (373, "忙"),
+    (374, "波波流泪"),
+    (375, "超级鼓掌"),
+    (376, "跺脚"),
+    (377, "嗨"),
+    (378, "企鹅笑哭"),
+    (379, "企鹅流泪"),
+    (380, "真棒"),
+    (381, "路过"),
+    (382, "emo"),
+    (383, "企鹅爱心"),
+    (384, "晚安"),
+    (385, "太气了"),
+    (386, "呜呜呜"),
+    (387, "太好笑"),
+    (388, "太头疼"),
+    (389, "太赞了"),
+    (390, "太头秃"),
+    (391, "太沧桑"),
+    (392, "龙年快乐"),
+    (393, "新年中龙"),
+    (394, "新年大龙"),
+    (395, "略略略")
 );
@@ -8,6 +8,8 @@ use crate::pb::msg;
 use crate::{push_builder_impl, to_elem_vec_impl};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum FlashImage {
     FriendImage(FriendImage),
     GroupImage(GroupImage),
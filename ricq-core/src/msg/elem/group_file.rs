@@ -0,0 +1,92 @@
+use std::fmt;
+
+use bytes::Buf;
+use prost::Message;
+
+use crate::pb::msg;
+
+/// 群文件，上传群文件后由服务器发出，只能接收，转发时原样保留
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupFile {
+    /// 文件路径，用于 `get_group_file_download`
+    pub file_id: String,
+    pub name: String,
+    pub size: i64,
+    pub bus_id: i32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::msg::serde_impl::pb_bytes"))]
+    pub(crate) raw: msg::TransElem,
+}
+
+impl GroupFile {
+    // elem_value: 1 + u16 长度 + ObjMsg
+    pub fn from_trans_elem(e: &msg::TransElem) -> Option<Self> {
+        if e.elem_type() != 24 {
+            return None;
+        }
+        let mut value = e.elem_value();
+        if value.len() <= 3 || value.get_u8() != 1 {
+            return None;
+        }
+        let len = value.get_u16() as usize;
+        let obj = msg::ObjMsg::decode(value.get(..len)?).ok()?;
+        let file = obj.msg_content_info.into_iter().next()?.msg_file?;
+        Some(Self {
+            file_id: String::from_utf8_lossy(&file.file_path).into_owned(),
+            name: file.file_name,
+            size: file.file_size,
+            bus_id: file.bus_id,
+            raw: e.clone(),
+        })
+    }
+}
+
+impl fmt::Display for GroupFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[文件]{}", self.name)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use bytes::BufMut;
+
+    use super::*;
+    use crate::msg::elem::RQElem;
+
+    pub(crate) fn group_file_elem() -> msg::elem::Elem {
+        let obj = msg::ObjMsg {
+            msg_type: 6,
+            msg_content_info: vec![msg::MsgContentInfo {
+                msg_file: Some(msg::MsgFile {
+                    bus_id: 102,
+                    file_path: b"/d7d1a2c4-7a1e-11ed-a6f8-5452007bd5ac".to_vec(),
+                    file_size: 1024,
+                    file_name: "a.txt".into(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+        .encode_to_vec();
+        let mut value = vec![1];
+        value.put_u16(obj.len() as u16);
+        value.put_slice(&obj);
+        msg::elem::Elem::TransElemInfo(msg::TransElem {
+            elem_type: Some(24),
+            elem_value: Some(value),
+        })
+    }
+
+    #[test]
+    fn test_group_file() {
+        let RQElem::GroupFile(parsed) = RQElem::from(group_file_elem()) else {
+            panic!("not a group file")
+        };
+        assert_eq!(parsed.file_id, "/d7d1a2c4-7a1e-11ed-a6f8-5452007bd5ac");
+        assert_eq!(parsed.name, "a.txt");
+        assert_eq!(parsed.size, 1024);
+        assert_eq!(parsed.bus_id, 102);
+    }
+}
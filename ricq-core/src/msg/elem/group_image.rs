@@ -82,7 +82,7 @@ impl From<msg::CustomFace> for GroupImage {
             return Self::default();
         }
         // guild image todo
-        return Self {
+        Self {
             file_id: custom_face.file_id() as i64,
             file_path: custom_face.file_path().to_owned(),
             size: custom_face.size(),
@@ -94,7 +94,7 @@ impl From<msg::CustomFace> for GroupImage {
             signature: custom_face.signature.unwrap_or_default(),
             server_ip: custom_face.server_ip.unwrap_or_default(),
             server_port: custom_face.server_port.unwrap_or_default(),
-        };
+        }
     }
 }
 
@@ -11,6 +11,7 @@ use crate::{push_builder_impl, to_elem_vec_impl};
 
 // Some of the share card message will be a LightApp with pkg id `com.tencent.structmsg`
 #[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LightApp {
     pub content: String,
 }
@@ -7,6 +7,7 @@ use crate::{push_builder_impl, to_elem_vec_impl};
 
 // 不需要实现 Display，因为后面一定会跟 Text
 #[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MarketFace {
     pub name: String,
     pub face_id: Vec<u8>,
@@ -57,6 +58,7 @@ impl From<msg::MarketFace> for MarketFace {
 }
 
 #[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Dice {
     pub value: i32, // range: [1, 6]
 }
@@ -105,6 +107,8 @@ impl PushElem for Dice {
 
 #[derive(Debug, Clone, derivative::Derivative)]
 #[derivative(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum FingerGuessing {
     #[derivative(Default)]
     Rock,
@@ -8,39 +8,57 @@ pub(crate) use text::flush_builder;
 pub use crate::msg::elem::{
     anonymous::Anonymous,
     at::At,
-    face::Face,
+    card::{
+        Card, FriendRecommendCard, GroupRecommendCard, LinkCard, LocationCard, MiniProgramCard,
+    },
+    face::{Face, FaceSticker},
     flash_image::FlashImage,
     friend_image::FriendImage,
+    group_file::GroupFile,
     group_image::GroupImage,
     light_app::LightApp,
     market_face::{Dice, FingerGuessing, MarketFace},
+    poke::Poke,
+    red_packet::RedPacket,
     reply::Reply,
     rich_msg::RichMsg,
     text::Text,
     video_file::VideoFile,
 };
+use crate::msg::{MessageChainBuilder, MessageElem, PushBuilder, PushElem};
 use crate::pb::msg;
+use crate::to_elem_vec_impl;
 
 mod anonymous;
 mod at;
+mod card;
 mod face;
 mod flash_image;
 mod friend_image;
+pub(crate) mod group_file;
 mod group_image;
 mod light_app;
 mod market_face;
+mod poke;
+pub(crate) mod red_packet;
 mod reply;
 mod rich_msg;
 mod text;
 mod video_file;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
 pub enum RQElem {
     At(at::At),
     Text(text::Text),
     Face(face::Face),
     MarketFace(market_face::MarketFace),
     Dice(market_face::Dice),
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::msg::serde_impl::finger_guessing")
+    )]
     FingerGuessing(market_face::FingerGuessing),
     LightApp(light_app::LightApp),
     RichMsg(rich_msg::RichMsg),
@@ -48,6 +66,11 @@ pub enum RQElem {
     GroupImage(group_image::GroupImage),
     FlashImage(flash_image::FlashImage),
     VideoFile(video_file::VideoFile),
+    GroupFile(group_file::GroupFile),
+    Poke(poke::Poke),
+    RedPacket(red_packet::RedPacket),
+    /// 未解析的消息元素，序列化为原始 protobuf
+    #[cfg_attr(feature = "serde", serde(with = "crate::msg::serde_impl::other_elem"))]
     Other(Box<msg::elem::Elem>),
 }
 
@@ -64,6 +87,13 @@ impl From<msg::elem::Elem> for RQElem {
             }
             msg::elem::Elem::Face(e) => RQElem::Face(face::Face::from(e)),
             msg::elem::Elem::CommonElem(ref e) => match e.service_type() {
+                2 => {
+                    if let Ok(poke) = msg::MsgElemInfoServtype2::decode(e.pb_elem()) {
+                        RQElem::Poke(poke::Poke::from(poke))
+                    } else {
+                        RQElem::Other(Box::new(elem))
+                    }
+                }
                 // TODO image
                 3 => {
                     if let Ok(flash) = msg::MsgElemInfoServtype3::decode(e.pb_elem()) {
@@ -85,6 +115,13 @@ impl From<msg::elem::Elem> for RQElem {
                         RQElem::Other(Box::new(elem))
                     }
                 }
+                37 => {
+                    if let Ok(super_face) = msg::MsgElemInfoServtype37::decode(e.pb_elem()) {
+                        RQElem::Face(face::Face::from(super_face))
+                    } else {
+                        RQElem::Other(Box::new(elem))
+                    }
+                }
                 _ => RQElem::Other(Box::new(elem)),
             },
             msg::elem::Elem::MarketFace(e) => {
@@ -103,6 +140,13 @@ impl From<msg::elem::Elem> for RQElem {
                 RQElem::FriendImage(friend_image::FriendImage::from(e))
             }
             msg::elem::Elem::CustomFace(e) => RQElem::GroupImage(group_image::GroupImage::from(e)),
+            msg::elem::Elem::TransElemInfo(ref e) => {
+                match group_file::GroupFile::from_trans_elem(e) {
+                    Some(file) => RQElem::GroupFile(file),
+                    None => RQElem::Other(Box::new(elem)),
+                }
+            }
+            msg::elem::Elem::QqWalletMsg(e) => RQElem::RedPacket(red_packet::RedPacket::from(e)),
             _ => RQElem::Other(Box::new(elem)),
         }
     }
@@ -119,6 +163,9 @@ impl fmt::Display for RQElem {
             RQElem::FlashImage(e) => fmt::Display::fmt(e, f),
             RQElem::LightApp(e) => fmt::Display::fmt(e, f),
             RQElem::RichMsg(e) => fmt::Display::fmt(e, f),
+            RQElem::GroupFile(e) => fmt::Display::fmt(e, f),
+            RQElem::Poke(e) => fmt::Display::fmt(e, f),
+            RQElem::RedPacket(e) => fmt::Display::fmt(e, f),
             _ => return Ok(()),
         }?;
         f.write_str(" ")
@@ -164,6 +211,9 @@ impl_from!(RichMsg, rich_msg::RichMsg);
 impl_from!(FriendImage, friend_image::FriendImage);
 impl_from!(GroupImage, group_image::GroupImage);
 impl_from!(FlashImage, flash_image::FlashImage);
+impl_from!(GroupFile, group_file::GroupFile);
+impl_from!(Poke, poke::Poke);
+impl_from!(RedPacket, red_packet::RedPacket);
 impl_from!(Other, Box<msg::elem::Elem>);
 
 impl From<String> for RQElem {
@@ -177,3 +227,41 @@ impl From<&str> for RQElem {
         RQElem::Text(text::Text::new(s.to_string()))
     }
 }
+
+impl PushElem for RQElem {
+    fn push_to(elem: Self, vec: &mut Vec<MessageElem>) {
+        match elem {
+            RQElem::At(e) => PushElem::push_to(e, vec),
+            RQElem::Text(e) => PushElem::push_to(e, vec),
+            RQElem::Face(e) => PushElem::push_to(e, vec),
+            RQElem::MarketFace(e) => PushElem::push_to(e, vec),
+            RQElem::Dice(e) => PushElem::push_to(e, vec),
+            RQElem::FingerGuessing(e) => PushElem::push_to(e, vec),
+            RQElem::LightApp(e) => PushElem::push_to(e, vec),
+            RQElem::RichMsg(e) => PushElem::push_to(e, vec),
+            RQElem::FriendImage(e) => PushElem::push_to(e, vec),
+            RQElem::GroupImage(e) => PushElem::push_to(e, vec),
+            RQElem::FlashImage(e) => PushElem::push_to(e, vec),
+            RQElem::VideoFile(e) => PushElem::push_to(e, vec),
+            RQElem::Poke(e) => PushElem::push_to(e, vec),
+            // 只能接收，原样写回收到的元素
+            RQElem::GroupFile(e) => vec.push(MessageElem::TransElemInfo(e.raw)),
+            RQElem::RedPacket(e) => vec.push(MessageElem::QqWalletMsg(*e.raw)),
+            RQElem::Other(e) => vec.push(*e),
+        }
+    }
+}
+
+impl PushBuilder for RQElem {
+    fn push_builder(elem: Self, builder: &mut MessageChainBuilder) {
+        match elem {
+            RQElem::Text(e) => PushBuilder::push_builder(e, builder),
+            e => {
+                builder.flush();
+                PushElem::push_to(e, &mut builder.elems);
+            }
+        }
+    }
+}
+
+to_elem_vec_impl!(RQElem);
@@ -0,0 +1,138 @@
+use std::fmt;
+
+use prost::Message;
+
+use crate::msg::{MessageChainBuilder, PushBuilder};
+use crate::msg::{MessageElem, PushElem};
+use crate::pb::msg;
+use crate::{push_builder_impl, to_elem_vec_impl};
+
+/// 消息中的戳一戳（窗口抖动、比心、点赞等），不是群里双击头像的戳一戳
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Poke {
+    /// 1 戳一戳，2 比心，3 点赞，4 心碎，5 666，6 放大招，126 会员戳一戳
+    pub poke_type: i32,
+    /// 会员戳一戳的 id，普通戳一戳为 -1
+    pub id: i32,
+    pub name: String,
+    pub strength: i32,
+}
+
+impl Default for Poke {
+    fn default() -> Self {
+        Self::new(1, -1)
+    }
+}
+
+impl Poke {
+    pub fn new(poke_type: i32, id: i32) -> Self {
+        Self {
+            poke_type,
+            id,
+            name: Self::name(poke_type, id).into(),
+            strength: 0,
+        }
+    }
+
+    pub fn name(poke_type: i32, id: i32) -> &'static str {
+        match (poke_type, id) {
+            (1, _) => "戳一戳",
+            (2, _) => "比心",
+            (3, _) => "点赞",
+            (4, _) => "心碎",
+            (5, _) => "666",
+            (6, _) => "放大招",
+            (126, 2001) => "抓一下",
+            (126, 2002) => "碎屏",
+            (126, 2003) => "勾引",
+            (126, 2004) => "手雷",
+            (126, 2005) => "结印",
+            (126, 2006) => "召唤术",
+            (126, 2007) => "玫瑰花",
+            (126, 2009) => "让你皮",
+            (126, 2011) => "宝贝球",
+            _ => "戳一戳",
+        }
+    }
+}
+
+impl PushElem for Poke {
+    fn push_to(elem: Self, vec: &mut Vec<MessageElem>) {
+        let pb_elem = msg::MsgElemInfoServtype2 {
+            poke_type: Some(elem.poke_type as u32),
+            vaspoke_id: Some(elem.id as u32),
+            vaspoke_name: Some(elem.name.as_bytes().to_vec()),
+            vaspoke_minver: Some(b"7.2.0".to_vec()),
+            poke_strength: Some(elem.strength as u32),
+            ..Default::default()
+        }
+        .encode_to_vec();
+        vec.push(MessageElem::CommonElem(msg::CommonElem {
+            service_type: Some(2),
+            pb_elem: Some(pb_elem),
+            business_type: Some(elem.poke_type),
+        }));
+        vec.push(MessageElem::Text(msg::Text {
+            str: Some(format!("[{}]请使用最新版手机QQ体验新功能", elem.name)),
+            ..Default::default()
+        }));
+    }
+}
+
+impl From<msg::MsgElemInfoServtype2> for Poke {
+    fn from(e: msg::MsgElemInfoServtype2) -> Self {
+        let poke_type = e.poke_type() as i32;
+        let id = e.vaspoke_id() as i32;
+        let name = String::from_utf8_lossy(e.vaspoke_name()).into_owned();
+        Self {
+            poke_type,
+            id,
+            name: if name.is_empty() {
+                Self::name(poke_type, id).into()
+            } else {
+                name
+            },
+            strength: e.poke_strength() as i32,
+        }
+    }
+}
+
+impl fmt::Display for Poke {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]", self.name)
+    }
+}
+
+to_elem_vec_impl!(Poke);
+push_builder_impl!(Poke);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::elem::RQElem;
+
+    #[test]
+    fn test_poke() {
+        let elem = msg::elem::Elem::CommonElem(msg::CommonElem {
+            service_type: Some(2),
+            pb_elem: Some(
+                msg::MsgElemInfoServtype2 {
+                    poke_type: Some(126),
+                    vaspoke_id: Some(2011),
+                    poke_strength: Some(2),
+                    ..Default::default()
+                }
+                .encode_to_vec(),
+            ),
+            business_type: Some(126),
+        });
+        let RQElem::Poke(parsed) = RQElem::from(elem) else {
+            panic!("not a poke")
+        };
+        assert_eq!(parsed.poke_type, 126);
+        assert_eq!(parsed.id, 2011);
+        assert_eq!(parsed.name, "宝贝球");
+        assert_eq!(parsed.strength, 2);
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+use crate::pb::msg;
+
+/// QQ 红包，只能接收，转发时原样保留
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RedPacket {
+    /// 祝福语，如“恭喜发财”
+    pub title: String,
+    /// 2 普通红包，3 拼手气红包，6 口令红包
+    pub msg_type: i32,
+    pub red_type: i32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::msg::serde_impl::pb_bytes"))]
+    pub(crate) raw: Box<msg::QqWalletMsg>,
+}
+
+impl From<msg::QqWalletMsg> for RedPacket {
+    fn from(e: msg::QqWalletMsg) -> Self {
+        let body = e.aio_body.clone().unwrap_or_default();
+        Self {
+            title: body
+                .receiver
+                .as_ref()
+                .or(body.sender.as_ref())
+                .map(|e| e.title().to_owned())
+                .unwrap_or_default(),
+            msg_type: body.msg_type(),
+            red_type: body.red_type(),
+            raw: Box::new(e),
+        }
+    }
+}
+
+impl fmt::Display for RedPacket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[QQ红包]{}", self.title)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::msg::elem::RQElem;
+
+    pub(crate) fn red_packet_elem() -> msg::elem::Elem {
+        msg::elem::Elem::QqWalletMsg(msg::QqWalletMsg {
+            aio_body: Some(msg::QqWalletAioBody {
+                receiver: Some(msg::QqWalletAioElem {
+                    title: Some("恭喜发财".into()),
+                    ..Default::default()
+                }),
+                msg_type: Some(2),
+                red_type: Some(2),
+                ..Default::default()
+            }),
+        })
+    }
+
+    #[test]
+    fn test_red_packet() {
+        let RQElem::RedPacket(parsed) = RQElem::from(red_packet_elem()) else {
+            panic!("not a red packet")
+        };
+        assert_eq!(parsed.title, "恭喜发财");
+        assert_eq!(parsed.msg_type, 2);
+        assert_eq!(parsed.red_type, 2);
+        assert_eq!(parsed.to_string(), "[QQ红包]恭喜发财");
+    }
+}
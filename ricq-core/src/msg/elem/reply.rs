@@ -2,10 +2,12 @@ use std::fmt;
 
 use crate::msg::{MessageChainBuilder, MessageElem, PushBuilder};
 use crate::pb::msg;
+use crate::structs::{FriendMessage, GroupMessage, GroupTempMessage};
 
 use super::super::MessageChain;
 
 #[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Reply {
     pub reply_seq: i32,
     pub sender: i64,
@@ -32,7 +34,7 @@ impl From<Reply> for MessageElem {
 
 impl PushBuilder for Reply {
     fn push_builder(elem: Self, builder: &mut MessageChainBuilder) {
-        let index = if let Some(MessageElem::AnonGroupMsg(..)) = builder.elems.get(0) {
+        let index = if let Some(MessageElem::AnonGroupMsg(..)) = builder.elems.first() {
             1
         } else {
             0
@@ -52,6 +54,26 @@ impl From<msg::SourceMsg> for Reply {
     }
 }
 
+/// 引用收到的消息
+macro_rules! impl_reply_from {
+    ($t:ty) => {
+        impl From<&$t> for Reply {
+            fn from(m: &$t) -> Self {
+                Self {
+                    reply_seq: m.seqs.first().cloned().unwrap_or_default(),
+                    sender: m.from_uin,
+                    time: m.time,
+                    elements: m.elements.clone(),
+                }
+            }
+        }
+    };
+}
+
+impl_reply_from!(GroupMessage);
+impl_reply_from!(FriendMessage);
+impl_reply_from!(GroupTempMessage);
+
 impl fmt::Display for Reply {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "[Reply: {}]", self.reply_seq)
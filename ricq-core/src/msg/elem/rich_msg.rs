@@ -12,6 +12,7 @@ use crate::pb::msg;
 use crate::{push_builder_impl, to_elem_vec_impl};
 
 #[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RichMsg {
     pub service_id: i32,
     pub template1: String,
@@ -5,6 +5,7 @@ use crate::pb::msg;
 use crate::to_elem_vec_impl;
 
 #[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Text {
     pub content: String,
 }
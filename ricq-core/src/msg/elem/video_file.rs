@@ -7,6 +7,7 @@ use crate::pb::msg;
 use crate::{push_builder_impl, to_elem_vec_impl};
 
 #[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VideoFile {
     pub name: String,
     pub uuid: Vec<u8>,
@@ -1,9 +1,26 @@
+use prost::Message;
+
 use crate::pb::msg;
 
 use super::MessageChain;
 
+/// 单条消息的大小上限，超过后需要分片或作为长消息发送
+pub const MAX_MESSAGE_SIZE: usize = 702;
+
 impl MessageChain {
-    // TODO test
+    /// 估算消息编码后的大小
+    pub fn estimate_size(&self) -> usize {
+        self.0
+            .iter()
+            .map(|e| {
+                msg::Elem {
+                    elem: Some(e.clone()),
+                }
+                .encoded_len()
+            })
+            .sum()
+    }
+
     // https://github.com/mamoe/mirai/blob/dev/mirai-core/src/commonMain/kotlin/network/protocol/packet/chat/receive/MessageSvc.PbSendMsg.kt#L68
     pub fn fragment(mut self) -> Vec<MessageChain> {
         let mut results = vec![];
@@ -55,3 +72,29 @@ impl MessageChain {
         results
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::elem::{At, Face, Text};
+
+    #[test]
+    fn test_fragment() {
+        let mut chain = MessageChain::default();
+        chain.push(Text::new("a".repeat(200)));
+        chain.push(At::new(12345));
+        chain.push(Face::new(14));
+        let parts = chain.fragment();
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0].brief(), "a".repeat(80));
+        assert_eq!(parts[2].brief(), "a".repeat(40));
+        assert_eq!(parts[3].0.len(), 2);
+    }
+
+    #[test]
+    fn test_estimate_size() {
+        assert_eq!(MessageChain::default().estimate_size(), 0);
+        let chain = MessageChain::new(Text::new("a".repeat(MAX_MESSAGE_SIZE)));
+        assert!(chain.estimate_size() > MAX_MESSAGE_SIZE);
+    }
+}
@@ -0,0 +1,578 @@
+//! 消息的文本标记，形如 `hello [at:12345][face:14][image:md5]`
+//!
+//! 标签内第一个参数为主参数，其余参数为 `key=value`，字符串为空、数字为 0 的参数可以省略。
+//! 文本中的 `\ [ ]` 以及参数中的 `\ [ ] , =` 需要用 `\` 转义。
+//! 图片的 `url` 只在收到的消息中存在，重新发送时不会保留。
+//! 文件和红包只能来自收到的消息，解析时只使用 `raw`（原始消息元素），其他参数仅用于展示。
+
+use std::fmt::{self, Write};
+use std::str::Chars;
+
+use prost::Message;
+
+use crate::hex::{decode_hex, encode_hex};
+use crate::msg::elem::{
+    At, Dice, Face, FaceSticker, FingerGuessing, FlashImage, FriendImage, GroupImage, LightApp,
+    MarketFace, Poke, RQElem, Reply, RichMsg, VideoFile,
+};
+use crate::msg::{is_compat_text, MessageChain, MessageChainBuilder};
+use crate::pb::msg;
+use crate::{RQError, RQResult};
+
+impl MessageChain {
+    /// 转换为文本标记，如 `hello [at:12345][face:14]`
+    pub fn to_markup(&self) -> String {
+        let mut markup = String::new();
+        if let Some(reply) = self.reply() {
+            markup.push_str(
+                &TagWriter::new("reply", reply.reply_seq)
+                    .num("sender", reply.sender)
+                    .num("time", reply.time)
+                    .arg("content", reply.elements.to_markup())
+                    .finish(),
+            );
+        }
+        let elems: Vec<RQElem> = self.clone().into_iter().collect();
+        for (i, elem) in elems.iter().enumerate() {
+            if is_compat_text(&elems, i) {
+                continue;
+            }
+            markup.push_str(&elem.to_markup());
+        }
+        markup
+    }
+
+    /// 从文本标记解析[`MessageChain`]
+    pub fn from_markup(markup: &str) -> RQResult<Self> {
+        let mut builder = MessageChainBuilder::new();
+        let mut chars = markup.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    builder.push_str(chars.next().unwrap_or('\\').encode_utf8(&mut [0; 4]));
+                }
+                '[' => read_tag(&mut chars)?.push_to(&mut builder)?,
+                _ => {
+                    builder.push_str(c.encode_utf8(&mut [0; 4]));
+                }
+            }
+        }
+        Ok(builder.build())
+    }
+}
+
+impl RQElem {
+    /// 转换为文本标记
+    pub fn to_markup(&self) -> String {
+        match self {
+            RQElem::Text(e) => escape(&e.content, false),
+            RQElem::At(e) => {
+                let tag = TagWriter::new("at", e.target);
+                if e.display == At::new(e.target).display {
+                    tag.finish()
+                } else {
+                    tag.arg("display", &e.display).finish()
+                }
+            }
+            RQElem::Face(e) => match &e.sticker {
+                Some(sticker) => TagWriter::new("face", e.index)
+                    .arg("name", &e.name)
+                    .arg("pack", &sticker.pack_id)
+                    .arg("sticker", &sticker.sticker_id)
+                    .num("sticker_type", sticker.sticker_type)
+                    .num("source_type", sticker.source_type)
+                    .arg("result", &sticker.result_id)
+                    .finish(),
+                None => TagWriter::new("face", e.index).finish(),
+            },
+            RQElem::MarketFace(e) => TagWriter::new("market_face", encode_hex(&e.face_id))
+                .arg("name", &e.name)
+                .num("tab_id", e.tab_id)
+                .num("item_type", e.item_type)
+                .num("sub_type", e.sub_type)
+                .num("media_type", e.media_type)
+                .arg("key", encode_hex(&e.encrypt_key))
+                .arg("magic", &e.magic_value)
+                .finish(),
+            RQElem::Dice(e) => TagWriter::new("dice", e.value).finish(),
+            RQElem::FingerGuessing(e) => TagWriter::new(
+                "finger_guessing",
+                match e {
+                    FingerGuessing::Rock => "rock",
+                    FingerGuessing::Scissors => "scissors",
+                    FingerGuessing::Paper => "paper",
+                },
+            )
+            .finish(),
+            RQElem::LightApp(e) => TagWriter::new("light_app", &e.content).finish(),
+            RQElem::RichMsg(e) => TagWriter::new("rich_msg", e.service_id)
+                .arg("template", &e.template1)
+                .finish(),
+            RQElem::FriendImage(e) => write_friend_image("friend_image", e),
+            RQElem::GroupImage(e) => write_group_image("image", e),
+            RQElem::FlashImage(FlashImage::FriendImage(e)) => {
+                write_friend_image("flash_friend_image", e)
+            }
+            RQElem::FlashImage(FlashImage::GroupImage(e)) => write_group_image("flash_image", e),
+            RQElem::VideoFile(e) => TagWriter::new("video", encode_hex(&e.uuid))
+                .num("size", e.size)
+                .num("thumb_size", e.thumb_size)
+                .arg("md5", encode_hex(&e.md5))
+                .arg("thumb_md5", encode_hex(&e.thumb_md5))
+                .finish(),
+            RQElem::GroupFile(e) => TagWriter::new("file", &e.file_id)
+                .arg("name", &e.name)
+                .num("size", e.size)
+                .num("bus_id", e.bus_id)
+                .arg("raw", encode_hex(&e.raw.encode_to_vec()))
+                .finish(),
+            RQElem::Poke(e) => TagWriter::new("poke", e.poke_type)
+                .num("id", e.id)
+                .arg("name", &e.name)
+                .num("strength", e.strength)
+                .finish(),
+            RQElem::RedPacket(e) => TagWriter::new("red_packet", &e.title)
+                .num("msg_type", e.msg_type)
+                .num("red_type", e.red_type)
+                .arg("raw", encode_hex(&e.raw.encode_to_vec()))
+                .finish(),
+            RQElem::Other(e) => TagWriter::new(
+                "other",
+                encode_hex(
+                    &msg::Elem {
+                        elem: Some(*e.clone()),
+                    }
+                    .encode_to_vec(),
+                ),
+            )
+            .finish(),
+        }
+    }
+}
+
+fn write_friend_image(kind: &str, e: &FriendImage) -> String {
+    TagWriter::new(kind, &e.res_id)
+        .arg("path", &e.file_path)
+        .arg("md5", encode_hex(&e.md5))
+        .num("size", e.size)
+        .num("width", e.width)
+        .num("height", e.height)
+        .num("type", e.image_type)
+        .arg("url", &e.orig_url)
+        .arg("download_path", &e.download_path)
+        .finish()
+}
+
+fn write_group_image(kind: &str, e: &GroupImage) -> String {
+    TagWriter::new(kind, encode_hex(&e.md5))
+        .arg("path", &e.file_path)
+        .num("file_id", e.file_id)
+        .num("size", e.size)
+        .num("width", e.width)
+        .num("height", e.height)
+        .num("type", e.image_type)
+        .arg("url", e.orig_url.as_deref().unwrap_or_default())
+        .finish()
+}
+
+fn escape(s: &str, arg: bool) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '\\' | '[' | ']') || (arg && matches!(c, ',' | '=')) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+struct TagWriter(String);
+
+impl TagWriter {
+    fn new(kind: &str, main: impl fmt::Display) -> Self {
+        Self(format!("[{kind}:{}", escape(&main.to_string(), true)))
+    }
+
+    /// 字符串参数，为空时省略
+    fn arg(mut self, key: &str, value: impl fmt::Display) -> Self {
+        let value = value.to_string();
+        if !value.is_empty() {
+            let _ = write!(self.0, ",{key}={}", escape(&value, true));
+        }
+        self
+    }
+
+    /// 数字参数，为默认值时省略
+    fn num<T: fmt::Display + Default + PartialEq>(self, key: &str, value: T) -> Self {
+        if value == T::default() {
+            return self;
+        }
+        self.arg(key, value)
+    }
+
+    fn finish(mut self) -> String {
+        self.0.push(']');
+        self.0
+    }
+}
+
+#[derive(Debug, Default)]
+struct Tag {
+    kind: String,
+    main: String,
+    args: Vec<(String, String)>,
+}
+
+// 读取 `[` 之后到 `]` 的内容
+fn read_tag(chars: &mut Chars) -> RQResult<Tag> {
+    let mut tag = Tag::default();
+    let mut field = String::new();
+    let mut key: Option<String> = None;
+    let mut in_kind = true;
+    let mut is_main = true;
+    loop {
+        let c = chars
+            .next()
+            .ok_or_else(|| RQError::Decode("unclosed markup tag".into()))?;
+        match c {
+            '\\' => field.push(
+                chars
+                    .next()
+                    .ok_or_else(|| RQError::Decode("unclosed markup tag".into()))?,
+            ),
+            ':' if in_kind => {
+                tag.kind = std::mem::take(&mut field);
+                in_kind = false;
+            }
+            '=' if !in_kind && !is_main && key.is_none() => key = Some(std::mem::take(&mut field)),
+            ',' | ']' if !in_kind => {
+                let value = std::mem::take(&mut field);
+                if is_main {
+                    tag.main = value;
+                    is_main = false;
+                } else if let Some(key) = key.take() {
+                    tag.args.push((key, value));
+                } else {
+                    return Err(RQError::Decode(format!(
+                        "markup argument without key: {value}"
+                    )));
+                }
+                if c == ']' {
+                    return Ok(tag);
+                }
+            }
+            ']' => {
+                tag.kind = field;
+                return Ok(tag);
+            }
+            _ => field.push(c),
+        }
+    }
+}
+
+impl Tag {
+    fn arg(&self, key: &str) -> &str {
+        self.args
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+            .unwrap_or_default()
+    }
+
+    fn parse<T: std::str::FromStr + Default>(&self, key: &str) -> RQResult<T> {
+        parse_value(key, self.arg(key))
+    }
+
+    fn hex(&self, key: &str) -> RQResult<Vec<u8>> {
+        parse_hex(key, self.arg(key))
+    }
+
+    fn push_to(self, builder: &mut MessageChainBuilder) -> RQResult<()> {
+        match self.kind.as_str() {
+            "at" => {
+                let mut at = At::new(parse_value("at", &self.main)?);
+                if !self.arg("display").is_empty() {
+                    at.display = self.arg("display").into();
+                }
+                builder.push(at);
+            }
+            "face" => {
+                let mut face = Face::new(parse_value("face", &self.main)?);
+                if !self.arg("sticker").is_empty() {
+                    if !self.arg("name").is_empty() {
+                        face.name = self.arg("name").into();
+                    }
+                    face.sticker = Some(FaceSticker {
+                        pack_id: self.arg("pack").into(),
+                        sticker_id: self.arg("sticker").into(),
+                        sticker_type: self.parse("sticker_type")?,
+                        source_type: self.parse("source_type")?,
+                        result_id: self.arg("result").into(),
+                    });
+                }
+                builder.push(face);
+            }
+            "market_face" => {
+                builder.push(MarketFace {
+                    name: self.arg("name").into(),
+                    face_id: parse_hex("market_face", &self.main)?,
+                    tab_id: self.parse("tab_id")?,
+                    item_type: self.parse("item_type")?,
+                    sub_type: self.parse("sub_type")?,
+                    media_type: self.parse("media_type")?,
+                    encrypt_key: self.hex("key")?,
+                    magic_value: self.arg("magic").into(),
+                });
+            }
+            "dice" => {
+                builder.push(Dice::new(parse_value("dice", &self.main)?));
+            }
+            "finger_guessing" => {
+                builder.push(match self.main.as_str() {
+                    "rock" => FingerGuessing::Rock,
+                    "scissors" => FingerGuessing::Scissors,
+                    "paper" => FingerGuessing::Paper,
+                    v => {
+                        return Err(RQError::Decode(format!(
+                            "invalid markup finger_guessing: {v}"
+                        )))
+                    }
+                });
+            }
+            "light_app" => {
+                builder.push(LightApp::new(self.main));
+            }
+            "rich_msg" => {
+                builder.push(RichMsg {
+                    service_id: parse_value("rich_msg", &self.main)?,
+                    template1: self.arg("template").into(),
+                });
+            }
+            "friend_image" => {
+                builder.push(self.friend_image()?);
+            }
+            "flash_friend_image" => {
+                builder.push(self.friend_image()?.flash());
+            }
+            "image" => {
+                builder.push(self.group_image()?);
+            }
+            "flash_image" => {
+                builder.push(self.group_image()?.flash());
+            }
+            "video" => {
+                builder.push(VideoFile {
+                    uuid: parse_hex("video", &self.main)?,
+                    size: self.parse("size")?,
+                    thumb_size: self.parse("thumb_size")?,
+                    md5: self.hex("md5")?,
+                    thumb_md5: self.hex("thumb_md5")?,
+                    ..Default::default()
+                });
+            }
+            "poke" => {
+                let mut poke = Poke::new(parse_value("poke", &self.main)?, self.parse("id")?);
+                if !self.arg("name").is_empty() {
+                    poke.name = self.arg("name").into();
+                }
+                poke.strength = self.parse("strength")?;
+                builder.push(poke);
+            }
+            "file" => {
+                let raw = msg::TransElem::decode(&*self.raw()?)?;
+                builder.push(RQElem::from(msg::elem::Elem::TransElemInfo(raw)));
+            }
+            "red_packet" => {
+                let raw = msg::QqWalletMsg::decode(&*self.raw()?)?;
+                builder.push(RQElem::from(msg::elem::Elem::QqWalletMsg(raw)));
+            }
+            "reply" => {
+                builder.push(Reply {
+                    reply_seq: parse_value("reply", &self.main)?,
+                    sender: self.parse("sender")?,
+                    time: self.parse("time")?,
+                    elements: MessageChain::from_markup(self.arg("content"))?,
+                });
+            }
+            "other" => {
+                let elem = msg::Elem::decode(&*parse_hex("other", &self.main)?)?;
+                if let Some(elem) = elem.elem {
+                    builder.flush();
+                    builder.elems.push(elem);
+                }
+            }
+            kind => return Err(RQError::Decode(format!("unknown markup tag: {kind}"))),
+        }
+        Ok(())
+    }
+
+    /// 只能接收的元素必须带有原始数据
+    fn raw(&self) -> RQResult<Vec<u8>> {
+        match self.hex("raw")? {
+            raw if raw.is_empty() => Err(RQError::Decode(format!(
+                "markup tag {} requires raw",
+                self.kind
+            ))),
+            raw => Ok(raw),
+        }
+    }
+
+    fn friend_image(&self) -> RQResult<FriendImage> {
+        Ok(FriendImage {
+            res_id: self.main.clone(),
+            file_path: self.arg("path").into(),
+            md5: self.hex("md5")?,
+            size: self.parse("size")?,
+            width: self.parse("width")?,
+            height: self.parse("height")?,
+            image_type: self.parse("type")?,
+            orig_url: self.arg("url").into(),
+            download_path: self.arg("download_path").into(),
+        })
+    }
+
+    fn group_image(&self) -> RQResult<GroupImage> {
+        let md5 = parse_hex("image", &self.main)?;
+        let file_path = match self.arg("path") {
+            "" => format!("{}.png", encode_hex(&md5)),
+            path => path.into(),
+        };
+        Ok(GroupImage {
+            file_path,
+            file_id: self.parse("file_id")?,
+            size: self.parse("size")?,
+            width: self.parse("width")?,
+            height: self.parse("height")?,
+            md5,
+            orig_url: match self.arg("url") {
+                "" => None,
+                url => Some(url.into()),
+            },
+            image_type: self.parse("type")?,
+            ..Default::default()
+        })
+    }
+}
+
+fn parse_value<T: std::str::FromStr + Default>(key: &str, value: &str) -> RQResult<T> {
+    if value.is_empty() {
+        return Ok(T::default());
+    }
+    value
+        .parse()
+        .map_err(|_| RQError::Decode(format!("invalid markup {key}: {value}")))
+}
+
+fn parse_hex(key: &str, value: &str) -> RQResult<Vec<u8>> {
+    if !value.len().is_multiple_of(2) || !value.is_ascii() {
+        return Err(RQError::Decode(format!("invalid markup {key}: {value}")));
+    }
+    decode_hex(value).map_err(|_| RQError::Decode(format!("invalid markup {key}: {value}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::elem::group_file::tests::group_file_elem;
+    use crate::msg::elem::red_packet::tests::red_packet_elem;
+    use crate::msg::elem::Text;
+    use crate::msg::MessageElem;
+
+    fn round_trip(markup: &str) {
+        let chain = MessageChain::from_markup(markup).unwrap();
+        assert_eq!(chain.to_markup(), markup);
+    }
+
+    #[test]
+    fn test_parse() {
+        let chain = MessageChain::from_markup("hello [at:12345][face:14]\\[not a tag\\]").unwrap();
+        let elems: Vec<RQElem> = chain.into_iter().collect();
+        assert_eq!(elems.len(), 4);
+        assert!(matches!(&elems[0], RQElem::Text(t) if t.content == "hello "));
+        assert!(
+            matches!(&elems[1], RQElem::At(at) if at.target == 12345 && at.display == "@12345")
+        );
+        assert!(matches!(&elems[2], RQElem::Face(face) if face.index == 14));
+        assert!(matches!(&elems[3], RQElem::Text(t) if t.content == "[not a tag]"));
+
+        let chain = MessageChain::from_markup("[image:0123456789abcdef]").unwrap();
+        match chain.into_iter().next() {
+            Some(RQElem::GroupImage(image)) => {
+                assert_eq!(
+                    image.md5,
+                    vec![0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]
+                );
+                assert_eq!(image.file_path, "0123456789abcdef.png");
+            }
+            e => panic!("unexpected {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        assert!(MessageChain::from_markup("[at:12345").is_err());
+        assert!(MessageChain::from_markup("[unknown:1]").is_err());
+        assert!(MessageChain::from_markup("[at:abc]").is_err());
+        assert!(MessageChain::from_markup("[image:123]").is_err());
+        assert!(MessageChain::from_markup("[face:1,2]").is_err());
+        assert!(MessageChain::from_markup("[file:/abc-def,name=a.txt]").is_err());
+        assert!(MessageChain::from_markup("[red_packet:恭喜发财]").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip("hello \\[world\\] a,b=c\\\\");
+        round_trip("[at:12345][at:0,display=@全体成员] [face:14][face:277]");
+        round_trip("[face:358,name=骰子,pack=1,sticker=33,sticker_type=2,source_type=1,result=4]");
+        round_trip("[dice:3][finger_guessing:paper]");
+        round_trip("[light_app:{\"app\":\"com.tencent.miniapp\"\\,\"a\":\"\\[\\]\"}]");
+        round_trip("[rich_msg:35,template=<msg a\\=\"1\"/>]");
+        round_trip(
+            "[image:0123456789abcdef,path=x.jpg,file_id=1,size=2,width=3,height=4,type=1000]",
+        );
+        round_trip("[flash_image:0123456789abcdef,path=x.jpg]");
+        round_trip("[friend_image:/1-2-3,path=x.jpg,md5=0123,size=2,width=3,height=4,type=1000,download_path=/1-2-3]");
+        round_trip("[flash_friend_image:/1-2-3]");
+        round_trip("[video:6162,size=2,thumb_size=3,md5=0123,thumb_md5=4567]");
+        round_trip("[reply:1,sender=2,time=3,content=hi\\[face:14\\]]text");
+        round_trip("[poke:1,id=-1,name=戳一戳][poke:126,id=2011,name=宝贝球,strength=2]");
+        round_trip("[poke:1,name=0]");
+    }
+
+    #[test]
+    fn test_round_trip_chain() {
+        let mut chain = MessageChain::default();
+        chain.push(Text::new("a[b]".into()));
+        chain.push(MarketFace {
+            name: "[表情]".into(),
+            face_id: vec![1, 2],
+            tab_id: 3,
+            item_type: 6,
+            sub_type: 3,
+            encrypt_key: vec![4, 5],
+            magic_value: "rscType?1;value=1".into(),
+            ..Default::default()
+        });
+        chain.0.push(MessageElem::GeneralFlags(msg::GeneralFlags {
+            pendant_id: Some(0),
+            ..Default::default()
+        }));
+        let markup = chain.to_markup();
+        assert_eq!(
+            markup,
+            "a\\[b\\][market_face:0102,name=\\[表情\\],tab_id=3,item_type=6,sub_type=3,key=0405,magic=rscType?1;value\\=1][other:aa0203880100]"
+        );
+        let parsed = MessageChain::from_markup(&markup).unwrap();
+        assert_eq!(parsed.0.len(), 4);
+        assert_eq!(parsed.to_markup(), markup);
+    }
+
+    #[test]
+    fn test_round_trip_receive_only() {
+        let chain = MessageChain(vec![group_file_elem(), red_packet_elem()]);
+        let markup = chain.to_markup();
+        assert!(markup.starts_with("[file:/d7d1a2c4-7a1e-11ed-a6f8-5452007bd5ac,name=a.txt,"));
+        let parsed = MessageChain::from_markup(&markup).unwrap();
+        assert_eq!(parsed.0, chain.0);
+    }
+}
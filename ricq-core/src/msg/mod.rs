@@ -8,6 +8,11 @@ use crate::pb::msg;
 pub mod elem;
 mod fragment;
 mod macros;
+mod markup;
+#[cfg(feature = "serde")]
+pub(crate) mod serde_impl;
+
+pub use fragment::MAX_MESSAGE_SIZE;
 
 pub type MessageElem = msg::elem::Elem;
 
@@ -84,6 +89,52 @@ impl MessageChain {
         let index = if self.anonymous().is_some() { 1 } else { 0 };
         self.0.insert(index, MessageElem::from(reply))
     }
+
+    /// 消息的简要文本，与客户端通知栏、卡片外显一致，如 `你好[图片]`
+    pub fn brief(&self) -> String {
+        self.clone()
+            .into_iter()
+            .map(|e| match e {
+                RQElem::At(e) => e.display,
+                RQElem::Text(e) => e.content,
+                RQElem::Face(e) => format!("[{}]", e.name),
+                RQElem::GroupImage(_) | RQElem::FriendImage(_) => "[图片]".into(),
+                RQElem::FlashImage(_) => "[闪照]".into(),
+                RQElem::VideoFile(_) => "[视频]".into(),
+                RQElem::LightApp(_) | RQElem::RichMsg(_) => "[卡片消息]".into(),
+                RQElem::GroupFile(e) => e.to_string(),
+                RQElem::Poke(e) => e.to_string(),
+                RQElem::RedPacket(e) => e.to_string(),
+                // 商城表情后面一定会跟 Text
+                _ => String::new(),
+            })
+            .collect()
+    }
+}
+
+/// 商城表情、闪照、戳一戳、超级表情后面和视频前面会跟兼容用的 Text，发送时会重新生成
+pub(crate) fn is_compat_text(elems: &[RQElem], index: usize) -> bool {
+    let RQElem::Text(text) = &elems[index] else {
+        return false;
+    };
+    let prev = index.checked_sub(1).map(|i| &elems[i]);
+    match (prev, elems.get(index + 1)) {
+        (Some(RQElem::MarketFace(face)), _) => face.name == text.content,
+        (Some(RQElem::Dice(_)), _) => text.content == "[骰子]",
+        (Some(RQElem::FingerGuessing(_)), _) => text.content == "[猜拳]",
+        (Some(RQElem::Poke(_)), _)
+        | (
+            Some(RQElem::Face(Face {
+                sticker: Some(_), ..
+            })),
+            _,
+        ) => text.content.contains("请使用最新版手机QQ体验新功能"),
+        (Some(RQElem::FlashImage(_)), _) => text.content == "[闪照]请使用新版手机QQ查看闪照。",
+        (_, Some(RQElem::VideoFile(_))) => {
+            text.content == "你的QQ暂不支持查看视频短片，请期待后续版本。"
+        }
+        _ => false,
+    }
 }
 
 impl<E> FromIterator<E> for MessageChain
@@ -260,6 +311,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_brief() {
+        let mut chain = MessageChain::default();
+        chain.with_reply(Reply::default());
+        chain.push(Text::new("hello".into()));
+        chain.push(At::new(12345));
+        chain.push(Face::new(1));
+        chain.push(Dice::new(1));
+        chain.push(GroupImage::default());
+        assert_eq!(chain.brief(), "hello@12345[撇嘴][骰子][图片]");
+    }
+
     #[test]
     fn test_builder() {
         let mut builder = MessageChainBuilder::new();
@@ -0,0 +1,212 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::msg::elem::{Anonymous, RQElem, Reply};
+use crate::msg::{is_compat_text, MessageChain, MessageElem, PushElem};
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ChainElem {
+    Reply(Reply),
+    Anonymous(Anonymous),
+    #[serde(untagged)]
+    Elem(RQElem),
+}
+
+// 引用回复和匿名信息在最前，兼容用的 Text 不序列化
+impl Serialize for MessageChain {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut chain: Vec<ChainElem> = self
+            .0
+            .iter()
+            .filter_map(|e| match e {
+                MessageElem::SrcMsg(e) => Some(ChainElem::Reply(e.clone().into())),
+                MessageElem::AnonGroupMsg(e) => Some(ChainElem::Anonymous(e.clone().into())),
+                _ => None,
+            })
+            .collect();
+        let elems: Vec<RQElem> = self.clone().into_iter().collect();
+        chain.extend(
+            elems
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !is_compat_text(&elems, *i))
+                .map(|(_, e)| ChainElem::Elem(e.clone())),
+        );
+        chain.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for MessageChain {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut elems = Vec::new();
+        for e in Vec::<ChainElem>::deserialize(deserializer)? {
+            match e {
+                ChainElem::Reply(e) => elems.push(e.into()),
+                ChainElem::Anonymous(e) => elems.push(e.into()),
+                ChainElem::Elem(e) => RQElem::push_to(e, &mut elems),
+            }
+        }
+        Ok(MessageChain(elems))
+    }
+}
+
+/// protobuf 消息序列化为 base64
+pub(crate) mod pb_bytes {
+    use super::*;
+
+    pub fn serialize<T, S>(message: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: prost::Message,
+        S: Serializer,
+    {
+        serializer.serialize_str(&STANDARD.encode(message.encode_to_vec()))
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: prost::Message + Default,
+        D: Deserializer<'de>,
+    {
+        let data = STANDARD
+            .decode(String::deserialize(deserializer)?)
+            .map_err(serde::de::Error::custom)?;
+        T::decode(&*data).map_err(serde::de::Error::custom)
+    }
+}
+
+/// [`RQElem::Other`] 序列化为 `{"type": "other", "data": base64}`
+pub(crate) mod other_elem {
+    use super::*;
+    use crate::pb::msg;
+
+    #[derive(Serialize, Deserialize)]
+    struct Other {
+        #[serde(with = "pb_bytes")]
+        data: msg::Elem,
+    }
+
+    pub fn serialize<S: Serializer>(
+        elem: &impl AsRef<msg::elem::Elem>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Other {
+            data: msg::Elem {
+                elem: Some(elem.as_ref().clone()),
+            },
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Box<msg::elem::Elem>, D::Error> {
+        Other::deserialize(deserializer)?
+            .data
+            .elem
+            .map(Box::new)
+            .ok_or_else(|| serde::de::Error::custom("empty elem"))
+    }
+}
+
+/// [`RQElem::FingerGuessing`] 序列化为 `{"type": "finger_guessing", "value": "rock"}`
+pub(crate) mod finger_guessing {
+    use super::*;
+    use crate::msg::elem::FingerGuessing;
+
+    #[derive(Serialize, Deserialize)]
+    struct Value {
+        value: FingerGuessing,
+    }
+
+    pub fn serialize<S: Serializer>(
+        value: &FingerGuessing,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        Value {
+            value: value.clone(),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<FingerGuessing, D::Error> {
+        Ok(Value::deserialize(deserializer)?.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::elem::group_file::tests::group_file_elem;
+    use crate::msg::elem::red_packet::tests::red_packet_elem;
+    use crate::msg::elem::{At, Dice, Face, FingerGuessing, GroupImage, Text};
+    use crate::pb::msg;
+
+    #[test]
+    fn test_round_trip() {
+        let mut chain = MessageChain::default();
+        chain.push(Text::new("hello".into()));
+        chain.push(At::new(12345));
+        chain.push(Face::new(14));
+        chain.push(Dice::new(3));
+        chain.push(FingerGuessing::Paper);
+        chain.push(
+            GroupImage {
+                md5: vec![1, 2, 3],
+                ..Default::default()
+            }
+            .flash(),
+        );
+        chain.0.push(MessageElem::GeneralFlags(msg::GeneralFlags {
+            pendant_id: Some(0),
+            ..Default::default()
+        }));
+        chain.with_reply(Reply {
+            reply_seq: 1,
+            sender: 2,
+            time: 3,
+            elements: MessageChain::new(Text::new("hi".into())),
+        });
+
+        let json = serde_json::to_value(&chain).unwrap();
+        assert_eq!(json[0]["type"], "reply");
+        assert_eq!(json[0]["elements"][0]["content"], "hi");
+        assert_eq!(
+            json[1],
+            serde_json::json!({"type": "text", "content": "hello"})
+        );
+        assert_eq!(json[2]["type"], "at");
+        assert_eq!(json[3]["type"], "face");
+        assert_eq!(json[4], serde_json::json!({"type": "dice", "value": 3}));
+        assert_eq!(
+            json[5],
+            serde_json::json!({"type": "finger_guessing", "value": "paper"})
+        );
+        assert_eq!(json[6]["type"], "flash_image");
+        assert_eq!(
+            json[7],
+            serde_json::json!({"type": "other", "data": "qgIDiAEA"})
+        );
+        assert_eq!(json.as_array().unwrap().len(), 8);
+
+        let parsed: MessageChain = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(parsed.0.len(), chain.0.len());
+        assert_eq!(parsed.reply().unwrap().reply_seq, 1);
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), json);
+    }
+
+    #[test]
+    fn test_round_trip_receive_only() {
+        let chain = MessageChain(vec![group_file_elem(), red_packet_elem()]);
+        let json = serde_json::to_value(&chain).unwrap();
+        assert_eq!(json[0]["type"], "group_file");
+        assert_eq!(json[0]["name"], "a.txt");
+        assert_eq!(json[1]["type"], "red_packet");
+        assert_eq!(json[1]["title"], "恭喜发财");
+        let parsed: MessageChain = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.0, chain.0);
+    }
+}
@@ -137,7 +137,7 @@ impl Transport {
         }
         if let Some(ref sign) = pkt.sign {
             w.put_u32(sign.len() as u32 + 4);
-            w.put_slice(&sign);
+            w.put_slice(sign);
         }
         w.put_u32(
             0x04 + self
@@ -1,15 +1,19 @@
 use bytes::Bytes;
 use std::time::Duration;
 
-pub use crate::command::multi_msg::{ForwardMessage, ForwardNode, MessageNode};
+pub use crate::command::multi_msg::{
+    ForwardMessage, ForwardNode, ForwardPreview, MessageNode, MultiMsgTarget,
+};
 pub use crate::command::oidb_svc::{
     LinkShare, MusicShare, MusicVersion, ProfileDetailUpdate, ShareTarget,
 };
 pub use crate::command::stat_svc::{CustomOnlineStatus, ExtOnlineStatus, OnlineStatus, Status};
+use crate::msg::elem::GroupFile;
 use crate::msg::MessageChain;
 use crate::{jce, pb};
 
 #[derive(Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AccountInfo {
     pub nickname: String,
     pub age: u8,
@@ -23,7 +27,8 @@ pub struct AddressInfo {
     pub file_storage_info: jce::FileStoragePushFSSvcList,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OtherClientInfo {
     pub app_id: i64,
     pub instance_id: i32,
@@ -47,6 +52,7 @@ pub struct BigDataReqSessionInfo {
 }
 
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupInfo {
     pub uin: i64,
     pub code: i64,
@@ -66,6 +72,7 @@ pub struct GroupInfo {
 }
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupMemberInfo {
     pub group_code: i64,
     pub uin: i64,
@@ -83,6 +90,8 @@ pub struct GroupMemberInfo {
 
 #[derive(Debug, Clone, derivative::Derivative)]
 #[derivative(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum GroupMemberPermission {
     Owner = 1,
     Administrator = 2,
@@ -90,8 +99,20 @@ pub enum GroupMemberPermission {
     Member = 3,
 }
 
+impl GroupMemberPermission {
+    /// 权限高低，群主 > 管理员 > 成员
+    pub fn level(&self) -> u8 {
+        match self {
+            GroupMemberPermission::Owner => 3,
+            GroupMemberPermission::Administrator => 2,
+            GroupMemberPermission::Member => 1,
+        }
+    }
+}
+
 /// 好友信息
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FriendInfo {
     pub uin: i64,
     pub nick: String,
@@ -102,6 +123,7 @@ pub struct FriendInfo {
 
 /// 好友分组信息
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FriendGroupInfo {
     pub group_id: u8,
     pub group_name: String,
@@ -111,6 +133,7 @@ pub struct FriendGroupInfo {
 }
 
 #[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SummaryCardInfo {
     pub uin: i64,
     pub sex: u8,
@@ -126,6 +149,7 @@ pub struct SummaryCardInfo {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FriendMessage {
     pub seqs: Vec<i32>,
     pub rands: Vec<i32>,
@@ -134,9 +158,14 @@ pub struct FriendMessage {
     pub from_uin: i64,
     pub from_nick: String,
     pub elements: MessageChain,
+    /// 离线期间收到的消息
+    pub offline: bool,
+    /// 本账号在其他设备发送的消息，target 是好友
+    pub from_self_device: bool,
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupMessage {
     pub seqs: Vec<i32>,
     pub rands: Vec<i32>,
@@ -146,9 +175,16 @@ pub struct GroupMessage {
     pub from_uin: i64,
     pub time: i32,
     pub elements: MessageChain,
+    /// 断线重连后补齐的消息
+    pub recovered: bool,
+    /// 离线期间收到的消息
+    pub offline: bool,
+    /// 本账号在其他设备发送的消息
+    pub from_self_device: bool,
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupTempMessage {
     pub seqs: Vec<i32>,
     pub rands: Vec<i32>,
@@ -157,15 +193,19 @@ pub struct GroupTempMessage {
     pub time: i32,
     pub elements: MessageChain,
     pub group_code: i64,
+    /// 离线期间收到的消息
+    pub offline: bool,
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NewMember {
     pub group_code: i64,
     pub member_uin: i64,
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupMute {
     pub group_code: i64,
     pub operator_uin: i64,
@@ -174,6 +214,7 @@ pub struct GroupMute {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FriendMessageRecall {
     pub msg_seq: i32,
     pub friend_uin: i64,
@@ -181,6 +222,7 @@ pub struct FriendMessageRecall {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupMessageRecall {
     pub msg_seq: i32,
     pub group_code: i64,
@@ -190,19 +232,76 @@ pub struct GroupMessageRecall {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupLeave {
     pub group_code: i64,
     pub member_uin: i64,
     pub operator_uin: Option<i64>,
 }
 
+/// 未处理的推送
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnhandledPush {
+    /// 包的 command，如 OnlinePush.ReqPush
+    pub command: String,
+    /// ReqPush 或同步消息的 msg_type
+    pub msg_type: Option<i64>,
+    /// 0x2dc 的 i_type，0x210 的 sub_msg_type
+    pub sub_type: Option<i64>,
+    /// 原始数据
+    pub body: Bytes,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OtherClientStatus {
+    pub info: OtherClientInfo,
+    /// true 上线，false 下线
+    pub online: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FriendInputStatus {
+    pub uin: i64,
+    /// true 正在输入，false 停止输入
+    pub inputting: bool,
+}
+
+#[derive(Debug, Clone, derivative::Derivative)]
+#[derivative(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ProfileChange {
+    /// 昵称
+    Nickname(String),
+    /// 头像，需要重新获取
+    #[derivative(Default)]
+    Avatar,
+    /// 个性签名
+    Signature(String),
+    /// 好友备注（其他客户端修改）
+    Remark(String),
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FriendProfileUpdate {
+    /// 可能是 bot 自己
+    pub uin: i64,
+    pub change: ProfileChange,
+}
+
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FriendPoke {
     pub sender: i64,
     pub receiver: i64,
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupPoke {
     pub group_code: i64,
     pub sender: i64,
@@ -210,6 +309,7 @@ pub struct GroupPoke {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupNameUpdate {
     pub group_code: i64,
     pub operator_uin: i64,
@@ -217,11 +317,13 @@ pub struct GroupNameUpdate {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DeleteFriend {
     pub uin: i64,
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MemberPermissionChange {
     pub group_code: i64,
     pub member_uin: i64,
@@ -229,6 +331,72 @@ pub struct MemberPermissionChange {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemberCardChange {
+    pub group_code: i64,
+    pub member_uin: i64,
+    /// 之前的群名片，未知时为 None
+    pub old_card: Option<String>,
+    pub new_card: String,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemberSpecialTitleChange {
+    pub group_code: i64,
+    pub member_uin: i64,
+    pub new_title: String,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupFileUpload {
+    pub group_code: i64,
+    pub uploader_uin: i64,
+    pub time: i32,
+    pub file: GroupFile,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupEssenceChange {
+    pub group_code: i64,
+    pub msg_seq: i32,
+    pub msg_rand: i32,
+    /// true 设为精华，false 移出精华
+    pub set: bool,
+    pub sender_uin: i64,
+    pub sender_nick: String,
+    pub operator_uin: i64,
+    pub operator_nick: String,
+    pub time: i32,
+}
+
+#[derive(Debug, Clone, derivative::Derivative)]
+#[derivative(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GroupHonor {
+    /// 龙王
+    #[derivative(Default)]
+    Talkative,
+    /// 群聊之火
+    Performer,
+    /// 快乐源泉
+    Emotion,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupHonorChange {
+    pub group_code: i64,
+    pub member_uin: i64,
+    pub member_nick: String,
+    pub honor: GroupHonor,
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupDisband {
     pub group_code: i64,
     pub operator_uin: i64,
@@ -236,6 +404,7 @@ pub struct GroupDisband {
 
 // 用于撤回
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MessageReceipt {
     pub seqs: Vec<i32>,
     pub rands: Vec<i32>,
@@ -243,9 +412,14 @@ pub struct MessageReceipt {
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct GroupAudio(pub pb::msg::Ptt);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GroupAudio(
+    #[cfg_attr(feature = "serde", serde(with = "crate::msg::serde_impl::pb_bytes"))]
+    pub  pb::msg::Ptt,
+);
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupAudioMessage {
     pub seqs: Vec<i32>,
     pub rands: Vec<i32>,
@@ -258,9 +432,14 @@ pub struct GroupAudioMessage {
 }
 
 #[derive(Debug, Clone, Default)]
-pub struct FriendAudio(pub pb::msg::Ptt);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FriendAudio(
+    #[cfg_attr(feature = "serde", serde(with = "crate::msg::serde_impl::pb_bytes"))]
+    pub  pb::msg::Ptt,
+);
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FriendAudioMessage {
     pub seqs: Vec<i32>,
     pub rands: Vec<i32>,
@@ -269,9 +448,12 @@ pub struct FriendAudioMessage {
     pub from_uin: i64,
     pub from_nick: String,
     pub audio: FriendAudio,
+    /// 离线期间收到的消息
+    pub offline: bool,
 }
 // 群文件总数
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupFileCount {
     pub is_full: bool,
     pub all_file_count: u32,
@@ -281,6 +463,7 @@ pub struct GroupFileCount {
 
 // 群文件列表
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupFileList {
     pub all_file_count: u32,
     pub is_end: bool,
@@ -290,6 +473,7 @@ pub struct GroupFileList {
 }
 // 群文件列表
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupFileItem {
     pub r#type: u32,
     pub folder_info: GroupFolderInfo,
@@ -298,6 +482,7 @@ pub struct GroupFileItem {
 
 // 群文件夹
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupFolderInfo {
     pub folder_id: String,
     pub parent_folder_id: String,
@@ -310,6 +495,7 @@ pub struct GroupFolderInfo {
 }
 // 群文件
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GroupFileInfo {
     pub file_id: String,
     pub file_name: String,
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::time::{Duration, UNIX_EPOCH};
 
 use bytes::BufMut;
 
@@ -8,14 +8,16 @@ use ricq_core::command::{friendlist::*, profile_service::*};
 use ricq_core::hex::encode_hex;
 use ricq_core::highway::BdhInput;
 use ricq_core::msg::elem::FriendImage;
-use ricq_core::msg::MessageChain;
+use ricq_core::msg::{MessageChain, MAX_MESSAGE_SIZE};
 use ricq_core::pb;
 use ricq_core::pb::msg::routing_head::RoutingHead;
 use ricq_core::structs::FriendAudio;
-use ricq_core::structs::MessageReceipt;
+use ricq_core::structs::{ForwardMessage, ForwardPreview, MultiMsgTarget};
+use ricq_core::structs::{FriendMessage, MessageReceipt};
 
+use crate::client::processor::c2c::friend_msg::parse_friend_message;
 use crate::structs::ImageInfo;
-use crate::{RQError, RQResult};
+use crate::{LongMessagePolicy, RQError, RQResult};
 
 impl super::super::Client {
     /// 获取好友请求
@@ -155,15 +157,141 @@ impl super::super::Client {
         Ok(())
     }
 
+    /// 获取与好友的漫游消息，返回 `before_time` 之前最近的最多 `count` 条，按时间排序
+    ///
+    /// `before_time` 为 0 时从最新的消息开始，语音消息不包含在内，无法解析的消息会被跳过
+    pub async fn get_friend_message_history(
+        &self,
+        uin: i64,
+        before_time: i64,
+        count: usize,
+    ) -> RQResult<Vec<FriendMessage>> {
+        let mut messages = Vec::new();
+        let mut last_msg_time = if before_time == 0 {
+            UNIX_EPOCH.elapsed().unwrap().as_secs() as i64
+        } else {
+            before_time
+        };
+        let mut random = 0;
+        while messages.len() < count {
+            let req = self.engine.read().await.build_get_one_day_roam_msg_request(
+                uin,
+                last_msg_time,
+                random,
+                (count - messages.len()).min(20) as u32,
+            );
+            let resp = self.send_and_wait(req).await?;
+            let resp = self
+                .engine
+                .read()
+                .await
+                .decode_get_one_day_roam_msg_response(resp.body)?;
+            let is_empty = resp.msgs.is_empty();
+            for msg in resp.msgs {
+                // 只保留普通消息
+                let rich_text = msg.body.as_ref().and_then(|b| b.rich_text.as_ref());
+                let head = msg.head.as_ref();
+                if rich_text.is_none_or(|r| r.ptt.is_some())
+                    || head.is_none_or(|h| h.to_uin.is_none() || h.msg_time.is_none())
+                {
+                    continue;
+                }
+                match parse_friend_message(msg) {
+                    Ok(message) => messages.push(message),
+                    Err(err) => tracing::warn!("skip undecodable friend {} message: {}", uin, err),
+                }
+            }
+            if resp.complete || is_empty || resp.last_msg_time >= last_msg_time {
+                break;
+            }
+            last_msg_time = resp.last_msg_time;
+            random = resp.random;
+        }
+        messages.sort_by_key(|m| (m.time, m.seqs.first().copied()));
+        messages.dedup_by_key(|m| (m.time, m.seqs.first().copied()));
+        if messages.len() > count {
+            messages.drain(..messages.len() - count);
+        }
+        Ok(messages)
+    }
+
     /// 发送好友消息
+    ///
+    /// 超出单条大小上限时按 [`LongMessagePolicy`] 处理
     pub async fn send_friend_message(
         &self,
         target: i64,
         message_chain: MessageChain,
     ) -> RQResult<MessageReceipt> {
+        if message_chain.estimate_size() > MAX_MESSAGE_SIZE {
+            match *self.long_message_policy.read().await {
+                LongMessagePolicy::Fragment => {
+                    return self
+                        .send_message_parts(
+                            RoutingHead::C2c(pb::msg::C2c {
+                                to_uin: Some(target),
+                            }),
+                            message_chain.fragment(),
+                            None,
+                        )
+                        .await;
+                }
+                LongMessagePolicy::LongMessage => {
+                    return self.send_friend_long_message(target, message_chain).await;
+                }
+                LongMessagePolicy::Disabled => {}
+            }
+        }
         self._send_friend_message(target, message_chain, None).await
     }
 
+    /// 发送好友长消息
+    pub async fn send_friend_long_message(
+        &self,
+        target: i64,
+        message_chain: MessageChain,
+    ) -> RQResult<MessageReceipt> {
+        self.send_friend_long_message_with_preview(target, message_chain, Default::default())
+            .await
+    }
+
+    /// 发送好友长消息，自定义卡片外显
+    pub async fn send_friend_long_message_with_preview(
+        &self,
+        target: i64,
+        message_chain: MessageChain,
+        preview: ForwardPreview,
+    ) -> RQResult<MessageReceipt> {
+        let chain = self
+            .build_long_message(MultiMsgTarget::Friend(target), message_chain, preview)
+            .await?;
+        self._send_friend_message(target, chain, None).await
+    }
+
+    /// 发送好友转发消息，外显标题根据消息发送者生成
+    pub async fn send_friend_forward_message(
+        &self,
+        target: i64,
+        msgs: Vec<ForwardMessage>,
+    ) -> RQResult<MessageReceipt> {
+        let preview = ForwardPreview::private_from_nodes(&msgs);
+        self.send_friend_forward_message_with_preview(target, msgs, preview)
+            .await
+    }
+
+    /// 发送好友转发消息，自定义卡片外显
+    pub async fn send_friend_forward_message_with_preview(
+        &self,
+        target: i64,
+        msgs: Vec<ForwardMessage>,
+        preview: ForwardPreview,
+    ) -> RQResult<MessageReceipt> {
+        let chain = self
+            .build_forward_message(MultiMsgTarget::Friend(target), msgs, preview)
+            .await?;
+        self._send_friend_message(target, chain, None).await
+    }
+
     /// 发送好友语音
     pub async fn send_friend_audio(
         &self,
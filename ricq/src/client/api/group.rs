@@ -7,21 +7,23 @@ use prost::Message;
 
 use ricq_core::command::common::PbToBytes;
 use ricq_core::command::img_store::GroupImageStoreResp;
-use ricq_core::command::multi_msg::gen_forward_preview;
+use ricq_core::command::online_push::GroupMessagePart;
 use ricq_core::command::{friendlist::*, oidb_svc::*, profile_service::*};
 use ricq_core::common::group_code2uin;
 use ricq_core::hex::encode_hex;
 use ricq_core::highway::BdhInput;
-use ricq_core::msg::elem::{Anonymous, GroupImage, RichMsg, VideoFile};
-use ricq_core::msg::MessageChain;
+use ricq_core::msg::elem::{Anonymous, GroupImage, VideoFile};
+use ricq_core::msg::{MessageChain, MAX_MESSAGE_SIZE};
 use ricq_core::pb;
 use ricq_core::pb::short_video::ShortVideoUploadRsp;
-use ricq_core::structs::{ForwardMessage, GroupFileCount, GroupFileList, MessageNode};
+use ricq_core::structs::{
+    ForwardMessage, ForwardPreview, GroupFileCount, GroupFileList, MultiMsgTarget,
+};
 use ricq_core::structs::{GroupAudio, GroupMemberPermission};
-use ricq_core::structs::{GroupInfo, GroupMemberInfo, MessageReceipt};
+use ricq_core::structs::{GroupInfo, GroupMemberInfo, GroupMessage, MessageReceipt};
 
 use crate::structs::ImageInfo;
-use crate::{RQError, RQResult};
+use crate::{LongMessagePolicy, RQError, RQResult};
 
 impl super::super::Client {
     /// 获取进群申请信息
@@ -96,11 +98,29 @@ impl super::super::Client {
     }
 
     /// 发送群消息
+    ///
+    /// 超出单条大小上限时按 [`LongMessagePolicy`] 处理
     pub async fn send_group_message(
         &self,
         group_code: i64,
         message_chain: MessageChain,
     ) -> RQResult<MessageReceipt> {
+        if message_chain.estimate_size() > MAX_MESSAGE_SIZE {
+            match *self.long_message_policy.read().await {
+                LongMessagePolicy::Fragment => {
+                    let parts = message_chain.fragment().into_iter().map(Into::into);
+                    return self
+                        ._send_group_message_parts(group_code, parts.collect(), None)
+                        .await;
+                }
+                LongMessagePolicy::LongMessage => {
+                    return self
+                        .send_group_long_message(group_code, message_chain)
+                        .await;
+                }
+                LongMessagePolicy::Disabled => {}
+            }
+        }
         self._send_group_message(group_code, message_chain.into(), None)
             .await
     }
@@ -121,30 +141,57 @@ impl super::super::Client {
         elems: Vec<pb::msg::Elem>,
         ptt: Option<pb::msg::Ptt>,
     ) -> RQResult<MessageReceipt> {
-        let ran = (rand::random::<u32>() >> 1) as i32;
-        let (tx, rx) = tokio::sync::oneshot::channel();
-        {
-            self.receipt_waiters.lock().await.cache_set(ran, tx);
-        }
-        let req = self
-            .engine
-            .read()
+        self._send_group_message_parts(group_code, vec![elems], ptt)
             .await
-            .build_group_sending_packet(group_code, elems, ptt, ran, 1, 0, 0, false);
-        let _ = self.send_and_wait(req).await?;
+    }
+
+    // 多条分片使用同一个 div_seq，接收方按 pkg_index 合并
+    async fn _send_group_message_parts(
+        &self,
+        group_code: i64,
+        parts: Vec<Vec<pb::msg::Elem>>,
+        ptt: Option<pb::msg::Ptt>,
+    ) -> RQResult<MessageReceipt> {
+        let pkg_num = parts.len() as i32;
+        let div_seq = if pkg_num > 1 {
+            (rand::random::<u32>() >> 1) as i32
+        } else {
+            0
+        };
+        let mut rands = Vec::with_capacity(parts.len());
+        let mut waiters = Vec::with_capacity(parts.len());
+        for (pkg_index, elems) in parts.into_iter().enumerate() {
+            let ran = (rand::random::<u32>() >> 1) as i32;
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            {
+                self.receipt_waiters.lock().await.cache_set(ran, tx);
+            }
+            let req = self.engine.read().await.build_group_sending_packet(
+                group_code,
+                elems,
+                ptt.clone(),
+                ran,
+                pkg_num,
+                pkg_index as i32,
+                div_seq,
+                false,
+            );
+            let _ = self.send_and_wait(req).await?;
+            rands.push(ran);
+            waiters.push(rx);
+        }
         let mut receipt = MessageReceipt {
-            seqs: vec![0],
-            rands: vec![ran],
+            seqs: vec![0; rands.len()],
+            rands,
             time: UNIX_EPOCH.elapsed().unwrap().as_secs() as i64,
         };
-        match tokio::time::timeout(Duration::from_secs(5), rx).await {
-            Ok(Ok(seq)) => {
-                if let Some(s) = receipt.seqs.first_mut() {
-                    *s = seq;
-                }
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        for (seq, rx) in receipt.seqs.iter_mut().zip(waiters) {
+            match tokio::time::timeout_at(deadline, rx).await {
+                Ok(Ok(s)) => *seq = s,
+                Ok(Err(_)) => {} //todo
+                Err(_) => {}
             }
-            Ok(Err(_)) => {} //todo
-            Err(_) => {}
         }
         Ok(receipt)
     }
@@ -268,6 +315,73 @@ impl super::super::Client {
         Ok(list)
     }
 
+    /// 获取群历史消息，包含 `begin_seq` 和 `end_seq`，按 seq 排序
+    ///
+    /// 每次最多请求 20 条，分片不完整的长消息和语音消息不包含在内，无法解析的消息会被跳过
+    pub async fn get_group_message_history(
+        &self,
+        group_code: i64,
+        begin_seq: i32,
+        end_seq: i32,
+    ) -> RQResult<Vec<GroupMessage>> {
+        let mut parts = Vec::new();
+        let mut begin = begin_seq;
+        while begin <= end_seq {
+            let end = end_seq.min(begin.saturating_add(19));
+            let req = self.engine.read().await.build_get_group_msg_request(
+                group_code,
+                begin as i64,
+                end as i64,
+            );
+            let resp = self.send_and_wait(req).await?;
+            let history = self
+                .engine
+                .read()
+                .await
+                .decode_get_group_msg_response(resp.body)?;
+            for err in history.errors {
+                tracing::warn!("skip undecodable group {} message: {}", group_code, err);
+            }
+            parts.extend(history.parts);
+            // 服务器返回的比请求的少时，从返回的位置继续
+            begin = if (begin..end).contains(&history.end_seq) {
+                history.end_seq + 1
+            } else {
+                end.saturating_add(1)
+            };
+            if end == i32::MAX {
+                break;
+            }
+        }
+
+        let mut complete = Vec::new();
+        let mut multi_parts: HashMap<i32, Vec<GroupMessagePart>> = HashMap::new();
+        // 语音消息需要单独解析为 GroupAudioMessage，这里不返回
+        for part in parts.into_iter().filter(|p| p.ptt.is_none()) {
+            if part.pkg_num > 1 {
+                multi_parts.entry(part.div_seq).or_default().push(part);
+            } else {
+                complete.push(vec![part]);
+            }
+        }
+        complete.extend(
+            multi_parts
+                .into_values()
+                .filter(|parts| parts.len() >= parts[0].pkg_num as usize),
+        );
+        let mut messages = Vec::with_capacity(complete.len());
+        for parts in complete {
+            match self.parse_group_message(parts).await {
+                Ok(message) => messages.push(message),
+                Err(err) => {
+                    tracing::warn!("skip undecodable group {} message: {}", group_code, err)
+                }
+            }
+        }
+        messages.sort_by_key(|m| m.seqs.first().copied());
+        Ok(messages)
+    }
+
     /// 标记群消息已读
     pub async fn mark_group_message_readed(&self, group_code: i64, seq: i32) -> RQResult<()> {
         let req = self
@@ -754,44 +868,20 @@ impl super::super::Client {
         group_code: i64,
         message_chain: MessageChain,
     ) -> RQResult<MessageReceipt> {
-        let brief = "[图片][图片][图片]"; // TODO brief
-        let res_id = self
-            .upload_msgs(
-                group_code,
-                vec![MessageNode {
-                    sender_id: self.uin().await,
-                    time: UNIX_EPOCH.elapsed().unwrap().as_secs() as i32,
-                    sender_name: self.account_info.read().await.nickname.clone(),
-                    elements: message_chain,
-                }
-                .into()],
-                true,
-            )
+        self.send_group_long_message_with_preview(group_code, message_chain, Default::default())
+            .await
+    }
+
+    /// 发送群长消息，自定义卡片外显
+    pub async fn send_group_long_message_with_preview(
+        &self,
+        group_code: i64,
+        message_chain: MessageChain,
+        preview: ForwardPreview,
+    ) -> RQResult<MessageReceipt> {
+        let chain = self
+            .build_long_message(MultiMsgTarget::Group(group_code), message_chain, preview)
             .await?;
-        let template=format!(
-            "<?xml version='1.0' encoding='UTF-8' standalone='yes' ?><msg serviceID=\"35\" templateID=\"1\" action=\"viewMultiMsg\" brief=\"{}\" m_resid=\"{}\" m_fileName=\"{}\" sourceMsgId=\"0\" url=\"\" flag=\"3\" adverSign=\"0\" multiMsgFlag=\"1\"><item layout=\"1\"><title>{}</title><hr hidden=\"false\" style=\"0\" /><summary>点击查看完整消息</summary></item><source name=\"聊天记录\" icon=\"\" action=\"\" appid=\"-1\" /></msg>",
-            brief,
-            res_id,
-            UNIX_EPOCH.elapsed().unwrap().as_millis(),
-            brief);
-        let mut chain = MessageChain::default();
-        chain.push(RichMsg {
-            service_id: 35,
-            template1: template,
-        });
-        chain.0.extend(vec![
-            pb::msg::elem::Elem::Text(pb::msg::Text {
-                str: Some("你的QQ暂不支持查看[转发多条消息]，请期待后续版本。".into()),
-                ..Default::default()
-            }),
-            pb::msg::elem::Elem::GeneralFlags(pb::msg::GeneralFlags {
-                long_text_flag: Some(1),
-                long_text_resid: Some(res_id),
-                pendant_id: Some(0),
-                pb_reserve: Some(vec![0x78, 0x00, 0xF8, 0x01, 0x00, 0xC8, 0x02, 0x00]), // TODO 15=73255?
-                ..Default::default()
-            }),
-        ]);
         self._send_group_message(group_code, chain.into(), None)
             .await
     }
@@ -802,30 +892,20 @@ impl super::super::Client {
         group_code: i64,
         msgs: Vec<ForwardMessage>,
     ) -> RQResult<MessageReceipt> {
-        let t_sum = msgs.len();
-        let preview = gen_forward_preview(&msgs);
-        let res_id = self.upload_msgs(group_code, msgs, false).await?;
-        // TODO friend template?
-        let template = format!(
-            r##"<?xml version='1.0' encoding='UTF-8' standalone='yes' ?><msg serviceID="35" templateID="1" action="viewMultiMsg" brief="[聊天记录]" m_resid="{}" m_fileName="{}" tSum="{}" sourceMsgId="0" url="" flag="3" adverSign="0" multiMsgFlag="0"><item layout="1" advertiser_id="0" aid="0"><title size="34" maxLines="2" lineSpace="12">群聊的聊天记录</title>{}<hr hidden="false" style="0" /><summary size="26" color="#777777">查看{}条转发消息</summary></item><source name="聊天记录" icon="" action="" appid="-1" /></msg>"##,
-            res_id,
-            UNIX_EPOCH.elapsed().unwrap().as_millis(), // TODO m_filename?
-            t_sum,
-            preview,
-            t_sum
-        );
-        let mut chain = MessageChain::default();
-        chain.push(RichMsg {
-            service_id: 35,
-            template1: template,
-        });
-        chain
-            .0
-            .push(pb::msg::elem::Elem::GeneralFlags(pb::msg::GeneralFlags {
-                pendant_id: Some(0),
-                pb_reserve: Some(vec![0x78, 0x00, 0xF8, 0x01, 0x00, 0xC8, 0x02, 0x00]),
-                ..Default::default()
-            }));
+        self.send_group_forward_message_with_preview(group_code, msgs, Default::default())
+            .await
+    }
+
+    /// 发送转发消息，自定义卡片外显
+    pub async fn send_group_forward_message_with_preview(
+        &self,
+        group_code: i64,
+        msgs: Vec<ForwardMessage>,
+        preview: ForwardPreview,
+    ) -> RQResult<MessageReceipt> {
+        let chain = self
+            .build_forward_message(MultiMsgTarget::Group(group_code), msgs, preview)
+            .await?;
         self._send_group_message(group_code, chain.into(), None)
             .await
     }
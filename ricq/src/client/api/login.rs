@@ -64,7 +64,7 @@ impl super::super::Client {
         let salt = QSignClient::calc_salt(
             uin as u64,
             &engine.transport.sig.guid,
-            &engine.transport.version.sdk_version,
+            engine.transport.version.sdk_version,
             sub_cmd as u32,
         );
         let resp = self
@@ -1,19 +1,23 @@
 use std::net::SocketAddr;
 use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use std::time::UNIX_EPOCH;
 
 use bytes::Bytes;
 use cached::Cached;
 
-use ricq_core::command::message_svc::MessageSyncResponse;
+use ricq_core::command::message_svc::{MessageFragment, MessageSyncResponse};
 use ricq_core::command::oidb_svc::*;
-use ricq_core::common::{group_code2uin, RQAddr};
+use ricq_core::common::RQAddr;
 use ricq_core::highway::BdhInput;
+use ricq_core::msg::elem::RichMsg;
 use ricq_core::msg::MessageChain;
 use ricq_core::pb;
 use ricq_core::structs::Status;
 use ricq_core::structs::SummaryCardInfo;
-use ricq_core::structs::{ForwardMessage, MessageReceipt};
+use ricq_core::structs::{
+    ForwardMessage, ForwardPreview, MessageNode, MessageReceipt, MultiMsgTarget,
+};
 
 use crate::jce::SvcDevLoginInfo;
 use crate::{RQError, RQResult};
@@ -66,14 +70,27 @@ impl super::Client {
         Ok(())
     }
 
-    /// 刷新客户端状态
-    pub async fn refresh_status(&self) -> RQResult<()> {
+    /// 刷新客户端状态，并上报离线期间收到的消息
+    pub async fn refresh_status(self: &Arc<Self>) -> RQResult<()> {
         let req = self
             .engine
             .read()
             .await
             .build_get_offline_msg_request_packet(self.last_message_time.load(Ordering::SeqCst));
-        let _resp = self.send_and_wait(req).await?;
+        let resp = self.send_and_wait(req).await?;
+        // 响应中的离线消息可能同时被 PushNotify 触发的同步拉取，由 c2c_cache 去重
+        match self
+            .engine
+            .read()
+            .await
+            .decode_get_offline_msg_response(resp.body)
+        {
+            Ok(msgs) => self.process_message_sync(msgs, true).await,
+            Err(err) => tracing::warn!("failed to decode offline message: {}", err),
+        }
+        // 剩余的离线消息通过 PbGetMsg 拉取，拉取后服务端会删除
+        let msgs = self.sync_all_message().await?;
+        self.process_message_sync(msgs, true).await;
         Ok(())
     }
 
@@ -284,7 +301,7 @@ impl super::Client {
     // 准备上传消息，获取 ukey, resid, ip, port
     async fn multi_msg_apply_up(
         &self,
-        dst_uin: i64,
+        target: MultiMsgTarget,
         data: &[u8],
         is_long: bool,
     ) -> RQResult<pb::multimsg::MultiMsgApplyUpRsp> {
@@ -292,7 +309,7 @@ impl super::Client {
             data.len() as i64,
             md5::compute(data).to_vec(),
             if is_long { 1 } else { 2 },
-            dst_uin,
+            target,
         );
         let resp = self.send_and_wait(req).await?;
         self.engine
@@ -301,21 +318,30 @@ impl super::Client {
             .decode_multi_msg_apply_up_resp(resp.body)
     }
 
-    // 上传长消息、转发消息 私聊未测试
+    // 上传群长消息、群转发消息
     pub async fn upload_msgs(
         &self,
         group_code: i64,
         msgs: Vec<ForwardMessage>,
         is_long: bool,
+    ) -> RQResult<String> {
+        self.upload_msgs_to(MultiMsgTarget::Group(group_code), msgs, is_long)
+            .await
+    }
+
+    // 上传长消息、转发消息，可发往群或好友
+    pub async fn upload_msgs_to(
+        &self,
+        target: MultiMsgTarget,
+        msgs: Vec<ForwardMessage>,
+        is_long: bool,
     ) -> RQResult<String> {
         let data = self
             .engine
             .read()
             .await
-            .calculate_validation_data(msgs, group_code);
-        let rsp = self
-            .multi_msg_apply_up(group_code2uin(group_code), &data, is_long)
-            .await?;
+            .calculate_validation_data(msgs, target);
+        let rsp = self.multi_msg_apply_up(target, &data, is_long).await?;
         let resid = rsp.msg_resid;
         if self.highway_session.read().await.session_key.is_empty() {
             return Err(RQError::EmptyField("highway_session_key is empty"));
@@ -326,11 +352,11 @@ impl super::Client {
             .zip(rsp.uint32_up_port)
             .map(|(ip, port)| RQAddr(ip as u32, port as u16))
             .collect();
-        let body =
-            self.engine
-                .read()
-                .await
-                .build_long_req(group_code2uin(group_code), data, rsp.msg_ukey);
+        let body = self
+            .engine
+            .read()
+            .await
+            .build_long_req(target, data, rsp.msg_ukey);
         for addr in addrs {
             match self
                 .highway_upload_bdh(
@@ -352,6 +378,80 @@ impl super::Client {
         Err(RQError::Other("failed to upload long message".into()))
     }
 
+    // 上传长消息，生成长消息卡片
+    async fn build_long_message(
+        &self,
+        target: MultiMsgTarget,
+        message_chain: MessageChain,
+        preview: ForwardPreview,
+    ) -> RQResult<MessageChain> {
+        let res_id = self
+            .upload_msgs_to(
+                target,
+                vec![MessageNode {
+                    sender_id: self.uin().await,
+                    time: UNIX_EPOCH.elapsed().unwrap().as_secs() as i32,
+                    sender_name: self.account_info.read().await.nickname.clone(),
+                    elements: message_chain.clone(),
+                }
+                .into()],
+                true,
+            )
+            .await?;
+        let template = preview.long_message_template(
+            &res_id,
+            &UNIX_EPOCH.elapsed().unwrap().as_millis().to_string(),
+            &message_chain,
+        );
+        let mut chain = MessageChain::default();
+        chain.push(RichMsg {
+            service_id: 35,
+            template1: template,
+        });
+        chain.0.extend(vec![
+            pb::msg::elem::Elem::Text(pb::msg::Text {
+                str: Some("你的QQ暂不支持查看[转发多条消息]，请期待后续版本。".into()),
+                ..Default::default()
+            }),
+            pb::msg::elem::Elem::GeneralFlags(pb::msg::GeneralFlags {
+                long_text_flag: Some(1),
+                long_text_resid: Some(res_id),
+                pendant_id: Some(0),
+                pb_reserve: Some(vec![0x78, 0x00, 0xF8, 0x01, 0x00, 0xC8, 0x02, 0x00]), // TODO 15=73255?
+                ..Default::default()
+            }),
+        ]);
+        Ok(chain)
+    }
+
+    // 上传转发消息，生成转发消息卡片
+    async fn build_forward_message(
+        &self,
+        target: MultiMsgTarget,
+        msgs: Vec<ForwardMessage>,
+        preview: ForwardPreview,
+    ) -> RQResult<MessageChain> {
+        let res_id = self.upload_msgs_to(target, msgs.clone(), false).await?;
+        let template = preview.forward_template(
+            &res_id,
+            &UNIX_EPOCH.elapsed().unwrap().as_millis().to_string(), // TODO m_filename?
+            &msgs,
+        );
+        let mut chain = MessageChain::default();
+        chain.push(RichMsg {
+            service_id: 35,
+            template1: template,
+        });
+        chain
+            .0
+            .push(pb::msg::elem::Elem::GeneralFlags(pb::msg::GeneralFlags {
+                pendant_id: Some(0),
+                pb_reserve: Some(vec![0x78, 0x00, 0xF8, 0x01, 0x00, 0xC8, 0x02, 0x00]),
+                ..Default::default()
+            }));
+        Ok(chain)
+    }
+
     // 获取转发消息下载地址和 key
     async fn multi_msg_apply_down(
         &self,
@@ -369,6 +469,7 @@ impl super::Client {
             .decode_multi_msg_apply_down_resp(resp.body)
     }
 
+    /// 下载转发消息、长消息
     pub async fn download_msgs(&self, res_id: String) -> RQResult<Vec<ForwardMessage>> {
         let mut resp = self.multi_msg_apply_down(res_id).await?;
         if resp.result != 0 {
@@ -377,23 +478,36 @@ impl super::Client {
                 resp.result
             )));
         }
-        let prefix=if let Some(pb::multimsg::ExternMsg { channel_type }) = resp.msg_extern_info && channel_type == 2 {
+        let prefix = if matches!(
+            resp.msg_extern_info,
+            Some(pb::multimsg::ExternMsg { channel_type: 2 })
+        ) {
             "https://ssl.htdata.qq.com".into()
         } else {
-            let addr = SocketAddr::from(RQAddr(resp.down_ip.pop().ok_or(RQError::EmptyField("down_ip"))?,resp.down_port.pop().ok_or(RQError::EmptyField("down_port"))? as u16));
+            let addr = SocketAddr::from(RQAddr(
+                resp.down_ip.pop().ok_or(RQError::EmptyField("down_ip"))?,
+                resp.down_port
+                    .pop()
+                    .ok_or(RQError::EmptyField("down_port"))? as u16,
+            ));
             format!("http://{addr}")
         };
-        let _url = format!(
+        let url = format!(
             "{}{}",
             prefix,
             String::from_utf8_lossy(&resp.thumb_down_para)
         );
-        let _encrypt_key = resp.msg_key;
-        // TODO get data and decrypt
-        // TODO decoder -> LongRspBody
-        // TODO uncompress
-        // TODO link message, convert to Vec<ForwardMessage>
-        todo!()
+        let data = reqwest::get(url)
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| RQError::Other(e.to_string()))?
+            .bytes()
+            .await
+            .map_err(|e| RQError::Other(e.to_string()))?;
+        self.engine
+            .read()
+            .await
+            .decode_multi_msg_download(data, &resp.msg_key)
     }
 
     /// 发送消息
@@ -402,26 +516,64 @@ impl super::Client {
         routing_head: pb::msg::routing_head::RoutingHead,
         message_chain: MessageChain,
         ptt: Option<pb::msg::Ptt>,
+    ) -> RQResult<MessageReceipt> {
+        self.send_message_parts(routing_head, vec![message_chain], ptt)
+            .await
+    }
+
+    // 多条分片使用同一个 div_seq，接收方按 pkg_index 合并
+    pub(crate) async fn send_message_parts(
+        &self,
+        routing_head: pb::msg::routing_head::RoutingHead,
+        parts: Vec<MessageChain>,
+        ptt: Option<pb::msg::Ptt>,
     ) -> RQResult<MessageReceipt> {
         let time = UNIX_EPOCH.elapsed().unwrap().as_secs() as i64;
-        let seq = self.engine.read().await.next_friend_seq();
-        let ran = (rand::random::<u32>() >> 1) as i32;
-        let (tx, _) = tokio::sync::oneshot::channel();
-        {
-            self.receipt_waiters.lock().await.cache_set(ran, tx);
+        let pkg_num = parts.len() as i32;
+        let div_seq = if pkg_num > 1 {
+            (rand::random::<u32>() >> 1) as i32
+        } else {
+            0
+        };
+        let mut seqs = Vec::with_capacity(parts.len());
+        let mut rands = Vec::with_capacity(parts.len());
+        for (pkg_index, message_chain) in parts.into_iter().enumerate() {
+            let seq = self.engine.read().await.next_friend_seq();
+            let ran = (rand::random::<u32>() >> 1) as i32;
+            let (tx, _) = tokio::sync::oneshot::channel();
+            {
+                self.receipt_waiters.lock().await.cache_set(ran, tx);
+            }
+            let req = if pkg_num > 1 {
+                self.engine.read().await.build_send_fragment_packet(
+                    routing_head.clone(),
+                    message_chain.into(),
+                    seq,
+                    ran,
+                    time,
+                    MessageFragment {
+                        pkg_num,
+                        pkg_index: pkg_index as i32,
+                        div_seq,
+                    },
+                )
+            } else {
+                self.engine.read().await.build_send_message_packet(
+                    routing_head.clone(),
+                    message_chain.into(),
+                    ptt.clone(),
+                    seq,
+                    ran,
+                    time,
+                )
+            };
+            self.send_and_wait(req).await?;
+            seqs.push(seq);
+            rands.push(ran);
         }
-        let req = self.engine.read().await.build_send_message_packet(
-            routing_head,
-            message_chain.into(),
-            ptt,
-            seq,
-            ran,
-            time,
-        );
-        self.send_and_wait(req).await?;
         let receipt = MessageReceipt {
-            seqs: vec![seq],
-            rands: vec![ran],
+            seqs,
+            rands,
             time: UNIX_EPOCH.elapsed().unwrap().as_secs() as i64,
         };
         // 除了群聊，都不需要等 receipt 的 seq
@@ -1,12 +1,18 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use ricq_core::command::profile_service::{JoinGroupRequest, NewFriendRequest, SelfInvited};
+use ricq_core::msg::elem::Reply;
+use ricq_core::msg::MessageChain;
 use ricq_core::structs::{
-    DeleteFriend, FriendAudioMessage, FriendInfo, FriendMessageRecall, FriendPoke,
-    GroupAudioMessage, GroupDisband, GroupLeave, GroupMessageRecall, GroupMute, GroupNameUpdate,
-    GroupPoke, GroupTempMessage, MemberPermissionChange, NewMember,
+    DeleteFriend, FriendAudioMessage, FriendInfo, FriendInputStatus, FriendMessageRecall,
+    FriendPoke, FriendProfileUpdate, GroupAudioMessage, GroupDisband, GroupEssenceChange,
+    GroupFileUpload, GroupHonorChange, GroupLeave, GroupMemberPermission, GroupMessageRecall,
+    GroupMute, GroupNameUpdate, GroupPoke, GroupTempMessage, MemberCardChange,
+    MemberPermissionChange, MemberSpecialTitleChange, MessageReceipt, NewMember, OtherClientStatus,
+    UnhandledPush,
 };
-use ricq_core::{jce, RQResult};
+use ricq_core::{jce, RQError, RQResult};
 
 use crate::client::NetworkStatus;
 use crate::structs::{FriendMessage, GroupMessage};
@@ -22,9 +28,30 @@ pub struct EventWithClient<T> {
 
 pub type GroupMessageEvent = EventWithClient<GroupMessage>;
 
+/// bot 需要是管理员，且权限高于 target
+async fn check_group_permission(client: &Client, group_code: i64, target: i64) -> RQResult<()> {
+    let bot = client
+        .get_group_member_info(group_code, client.uin().await)
+        .await?;
+    if bot.permission.level() <= GroupMemberPermission::Member.level() {
+        return Err(RQError::PermissionDenied("bot is not group administrator"));
+    }
+    let member = client.get_group_member_info(group_code, target).await?;
+    if bot.permission.level() <= member.permission.level() {
+        return Err(RQError::PermissionDenied(
+            "bot permission is not higher than target",
+        ));
+    }
+    Ok(())
+}
+
 impl GroupMessageEvent {
+    /// 撤回消息，不是 bot 自己的消息时需要管理员权限
     pub async fn recall(&self) -> RQResult<()> {
-        // TODO check permission
+        if self.inner.from_uin != self.client.uin().await {
+            check_group_permission(&self.client, self.inner.group_code, self.inner.from_uin)
+                .await?;
+        }
         self.client
             .recall_group_message(
                 self.inner.group_code,
@@ -33,10 +60,165 @@ impl GroupMessageEvent {
             )
             .await
     }
+
+    /// 引用这条消息
+    pub fn quote(&self) -> Reply {
+        Reply::from(&self.inner)
+    }
+
+    /// 引用这条消息回复
+    pub async fn reply(&self, mut message: MessageChain) -> RQResult<MessageReceipt> {
+        message.with_reply(self.quote());
+        self.reply_plain(message).await
+    }
+
+    /// 不引用，直接发送到群
+    pub async fn reply_plain(&self, message: MessageChain) -> RQResult<MessageReceipt> {
+        self.client
+            .send_group_message(self.inner.group_code, message)
+            .await
+    }
+
+    /// 禁言发送者，duration 为 0 时解除禁言
+    pub async fn mute_sender(&self, duration: Duration) -> RQResult<()> {
+        check_group_permission(&self.client, self.inner.group_code, self.inner.from_uin).await?;
+        self.client
+            .group_mute(self.inner.group_code, self.inner.from_uin, duration)
+            .await
+    }
+
+    /// 踢出发送者，block 为 true 时不再接受其加群申请
+    pub async fn kick_sender(&self, block: bool) -> RQResult<()> {
+        check_group_permission(&self.client, self.inner.group_code, self.inner.from_uin).await?;
+        self.client
+            .group_kick(self.inner.group_code, vec![self.inner.from_uin], "", block)
+            .await
+    }
+
+    /// 设为精华消息
+    pub async fn set_essence(&self) -> RQResult<()> {
+        let resp = self
+            .client
+            .operate_group_essence(
+                self.inner.group_code,
+                self.inner.seqs.first().cloned().unwrap_or_default(),
+                self.inner.rands.first().cloned().unwrap_or_default(),
+                true,
+            )
+            .await?;
+        match resp.error_code() {
+            0 => Ok(()),
+            code => Err(RQError::UnsuccessfulRetCode(code as i32)),
+        }
+    }
+
+    /// 戳一戳发送者
+    pub async fn poke_sender(&self) -> RQResult<()> {
+        self.client
+            .group_poke(self.inner.group_code, self.inner.from_uin)
+            .await
+    }
+
+    /// 等待同一群内同一成员的下一条消息
+    pub async fn next_reply(&self, timeout: Duration) -> RQResult<GroupMessageEvent> {
+        let (group_code, from_uin) = (self.inner.group_code, self.inner.from_uin);
+        self.client
+            .wait_for_group_message(
+                move |m| m.group_code == group_code && m.from_uin == from_uin,
+                timeout,
+            )
+            .await
+    }
 }
 
 pub type FriendMessageEvent = EventWithClient<FriendMessage>;
+
+impl FriendMessageEvent {
+    /// 对话的好友，其他设备发送的消息为接收者
+    pub fn friend_uin(&self) -> i64 {
+        if self.inner.from_self_device {
+            self.inner.target
+        } else {
+            self.inner.from_uin
+        }
+    }
+
+    /// 引用这条消息
+    pub fn quote(&self) -> Reply {
+        Reply::from(&self.inner)
+    }
+
+    /// 引用这条消息回复
+    pub async fn reply(&self, mut message: MessageChain) -> RQResult<MessageReceipt> {
+        message.with_reply(self.quote());
+        self.reply_plain(message).await
+    }
+
+    /// 不引用，直接发送给好友
+    pub async fn reply_plain(&self, message: MessageChain) -> RQResult<MessageReceipt> {
+        self.client
+            .send_friend_message(self.friend_uin(), message)
+            .await
+    }
+
+    /// 戳一戳好友
+    pub async fn poke_sender(&self) -> RQResult<()> {
+        self.client.friend_poke(self.friend_uin()).await
+    }
+
+    /// 等待同一好友的下一条消息
+    pub async fn next_reply(&self, timeout: Duration) -> RQResult<FriendMessageEvent> {
+        let from_uin = self.friend_uin();
+        self.client
+            .wait_for_friend_message(move |m| m.from_uin == from_uin, timeout)
+            .await
+    }
+}
+
 pub type GroupTempMessageEvent = EventWithClient<GroupTempMessage>;
+
+impl GroupTempMessageEvent {
+    /// 引用这条消息
+    pub fn quote(&self) -> Reply {
+        Reply::from(&self.inner)
+    }
+
+    /// 引用这条消息回复
+    pub async fn reply(&self, mut message: MessageChain) -> RQResult<MessageReceipt> {
+        message.with_reply(self.quote());
+        self.reply_plain(message).await
+    }
+
+    /// 不引用，直接发送临时消息
+    pub async fn reply_plain(&self, message: MessageChain) -> RQResult<MessageReceipt> {
+        self.client
+            .send_group_temp_message(self.inner.group_code, self.inner.from_uin, message)
+            .await
+    }
+
+    /// 在来源群中戳一戳发送者
+    pub async fn poke_sender(&self) -> RQResult<()> {
+        self.client
+            .group_poke(self.inner.group_code, self.inner.from_uin)
+            .await
+    }
+
+    /// 在来源群中禁言发送者，duration 为 0 时解除禁言
+    pub async fn mute_sender(&self, duration: Duration) -> RQResult<()> {
+        check_group_permission(&self.client, self.inner.group_code, self.inner.from_uin).await?;
+        self.client
+            .group_mute(self.inner.group_code, self.inner.from_uin, duration)
+            .await
+    }
+
+    /// 从来源群中踢出发送者，block 为 true 时不再接受其加群申请
+    pub async fn kick_sender(&self, block: bool) -> RQResult<()> {
+        check_group_permission(&self.client, self.inner.group_code, self.inner.from_uin).await?;
+        self.client
+            .group_kick(self.inner.group_code, vec![self.inner.from_uin], "", block)
+            .await
+    }
+}
 pub type JoinGroupRequestEvent = EventWithClient<JoinGroupRequest>;
 
 impl JoinGroupRequestEvent {
@@ -95,10 +277,16 @@ pub type NewFriendEvent = EventWithClient<FriendInfo>;
 pub type GroupLeaveEvent = EventWithClient<GroupLeave>;
 pub type GroupDisbandEvent = EventWithClient<GroupDisband>;
 pub type FriendPokeEvent = EventWithClient<FriendPoke>;
+pub type UnhandledEvent = EventWithClient<UnhandledPush>;
+pub type OtherClientStatusEvent = EventWithClient<OtherClientStatus>;
+pub type FriendInputStatusEvent = EventWithClient<FriendInputStatus>;
+pub type FriendProfileUpdateEvent = EventWithClient<FriendProfileUpdate>;
 pub type GroupPokeEvent = EventWithClient<GroupPoke>;
 pub type GroupNameUpdateEvent = EventWithClient<GroupNameUpdate>;
 pub type DeleteFriendEvent = EventWithClient<DeleteFriend>;
 pub type MemberPermissionChangeEvent = EventWithClient<MemberPermissionChange>;
+pub type MemberCardChangeEvent = EventWithClient<MemberCardChange>;
+pub type MemberSpecialTitleChangeEvent = EventWithClient<MemberSpecialTitleChange>;
 pub type SelfInvitedEvent = EventWithClient<SelfInvited>;
 pub type GroupAudioMessageEvent = EventWithClient<GroupAudioMessage>;
 
@@ -120,6 +308,24 @@ impl FriendAudioMessageEvent {
     }
 }
 
+pub type GroupEssenceChangeEvent = EventWithClient<GroupEssenceChange>;
+pub type GroupHonorChangeEvent = EventWithClient<GroupHonorChange>;
+pub type GroupFileUploadEvent = EventWithClient<GroupFileUpload>;
+
+impl GroupFileUploadEvent {
+    /// 获取文件下载链接
+    pub async fn download_url(&self) -> RQResult<String> {
+        self.client
+            .get_group_file_download(
+                self.inner.group_code,
+                &self.inner.file.file_id,
+                self.inner.file.bus_id as u32,
+                &self.inner.file.name,
+            )
+            .await
+    }
+}
+
 pub type KickedOfflineEvent = EventWithClient<jce::RequestPushForceOffline>;
 pub type MSFOfflineEvent = EventWithClient<jce::RequestMSFForceOffline>;
 
@@ -149,3 +355,43 @@ impl ClientDisconnect {
         self.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::client::stream::tests::new_client;
+
+    #[test]
+    fn test_quote() {
+        let event = GroupMessageEvent {
+            client: Arc::new(new_client()),
+            inner: GroupMessage {
+                seqs: vec![100, 101],
+                from_uin: 10000,
+                time: 1600000000,
+                ..Default::default()
+            },
+        };
+        let quote = event.quote();
+        assert_eq!(quote.reply_seq, 100);
+        assert_eq!(quote.sender, 10000);
+        assert_eq!(quote.time, 1600000000);
+    }
+
+    #[test]
+    fn test_friend_uin() {
+        let mut event = FriendMessageEvent {
+            client: Arc::new(new_client()),
+            inner: FriendMessage {
+                from_uin: 10000,
+                target: 20000,
+                ..Default::default()
+            },
+        };
+        assert_eq!(event.friend_uin(), 10000);
+        event.inner.from_self_device = true;
+        assert_eq!(event.friend_uin(), 20000);
+    }
+}
@@ -0,0 +1,484 @@
+use std::any::Any;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use cached::{Cached, TimedCache};
+use futures_util::future::join_all;
+use futures_util::FutureExt;
+use tokio::sync::Mutex;
+
+use super::{Handler, QEvent};
+
+pub type BoxedHandler = Box<dyn Handler + Send + Sync + 'static>;
+
+#[async_trait]
+impl Handler for BoxedHandler {
+    async fn handle(&self, event: QEvent) {
+        self.as_ref().handle(event).await
+    }
+}
+
+#[async_trait]
+impl<H> Handler for Arc<H>
+where
+    H: Handler + Send + ?Sized,
+{
+    async fn handle(&self, event: QEvent) {
+        self.as_ref().handle(event).await
+    }
+}
+
+/// 只处理满足条件的事件
+pub struct Filter<H, F> {
+    inner: H,
+    predicate: F,
+}
+
+impl<H, F> Filter<H, F> {
+    pub fn new(inner: H, predicate: F) -> Self {
+        Self { inner, predicate }
+    }
+}
+
+#[async_trait]
+impl<H, F> Handler for Filter<H, F>
+where
+    H: Handler + Send,
+    F: Fn(&QEvent) -> bool + Send + Sync,
+{
+    async fn handle(&self, event: QEvent) {
+        if (self.predicate)(&event) {
+            self.inner.handle(event).await
+        }
+    }
+}
+
+/// 依次交给两个 Handler 处理
+pub struct Chain<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Chain<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+}
+
+#[async_trait]
+impl<A, B> Handler for Chain<A, B>
+where
+    A: Handler + Send,
+    B: Handler + Send,
+{
+    async fn handle(&self, event: QEvent) {
+        self.first.handle(event.clone()).await;
+        self.second.handle(event).await;
+    }
+}
+
+/// 同时交给多个 Handler 处理
+#[derive(Default)]
+pub struct FanOut {
+    handlers: Vec<BoxedHandler>,
+}
+
+impl FanOut {
+    pub fn new(handlers: Vec<BoxedHandler>) -> Self {
+        Self { handlers }
+    }
+
+    pub fn push<H>(&mut self, handler: H)
+    where
+        H: Handler + Send + Sync + 'static,
+    {
+        self.handlers.push(Box::new(handler));
+    }
+}
+
+#[async_trait]
+impl Handler for FanOut {
+    async fn handle(&self, event: QEvent) {
+        join_all(self.handlers.iter().map(|h| h.handle(event.clone()))).await;
+    }
+}
+
+type DedupKey = (&'static str, i64, i64, i32);
+
+/// 消息和撤回事件的去重 key：(事件名, 群号/发送者, 好友消息的接收者, seq)
+///
+/// 本账号在其他设备发给不同好友的消息发送者相同，需要按接收者区分
+fn dedup_key(event: &QEvent) -> Option<DedupKey> {
+    let (peer, target, seq) = match event {
+        QEvent::GroupMessage(e) => (e.inner.group_code, 0, *e.inner.seqs.first()?),
+        QEvent::GroupAudioMessage(e) => (e.inner.group_code, 0, *e.inner.seqs.first()?),
+        QEvent::FriendMessage(e) => (e.inner.from_uin, e.inner.target, *e.inner.seqs.first()?),
+        QEvent::FriendAudioMessage(e) => (e.inner.from_uin, e.inner.target, *e.inner.seqs.first()?),
+        QEvent::GroupTempMessage(e) => (e.inner.from_uin, 0, *e.inner.seqs.first()?),
+        QEvent::GroupMessageRecall(e) => (e.inner.group_code, 0, e.inner.msg_seq),
+        QEvent::FriendMessageRecall(e) => (e.inner.friend_uin, 0, e.inner.msg_seq),
+        _ => return None,
+    };
+    Some((event.kind(), peer, target, seq))
+}
+
+/// 丢弃一段时间内重复的消息（按 seq 判断），其他事件不受影响
+///
+/// lifespan 按整秒计算，不足 1 秒按 1 秒处理
+pub struct Dedup<H> {
+    inner: H,
+    seen: Mutex<TimedCache<DedupKey, ()>>,
+}
+
+impl<H> Dedup<H> {
+    pub fn new(inner: H, lifespan: Duration) -> Self {
+        Self {
+            inner,
+            seen: Mutex::new(TimedCache::with_lifespan(lifespan.as_secs().max(1))),
+        }
+    }
+}
+
+#[async_trait]
+impl<H> Handler for Dedup<H>
+where
+    H: Handler + Send,
+{
+    async fn handle(&self, event: QEvent) {
+        if let Some(key) = dedup_key(&event) {
+            let mut seen = self.seen.lock().await;
+            // cache_set 不计入 miss，先 cache_get 才能按 miss 数清理过期的 key
+            if seen.cache_get(&key).is_some() {
+                tracing::debug!("duplicate event dropped: {:?}", key);
+                return;
+            }
+            seen.cache_set(key, ());
+            if seen.cache_misses().unwrap_or_default() > 1000 {
+                seen.flush();
+                seen.cache_reset_metrics();
+            }
+        }
+        self.inner.handle(event).await
+    }
+}
+
+/// 每个群在 period 内最多处理 max 个事件，超出的丢弃，非群事件不受影响
+pub struct GroupRateLimit<H> {
+    inner: H,
+    max: usize,
+    period: Duration,
+    history: Mutex<HashMap<i64, VecDeque<Instant>>>,
+}
+
+impl<H> GroupRateLimit<H> {
+    pub fn new(inner: H, max: usize, period: Duration) -> Self {
+        Self {
+            inner,
+            max,
+            period,
+            history: Default::default(),
+        }
+    }
+
+    async fn acquire(&self, group_code: i64) -> bool {
+        let now = Instant::now();
+        let mut history = self.history.lock().await;
+        let times = history.entry(group_code).or_default();
+        while times
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= self.period)
+        {
+            times.pop_front();
+        }
+        if times.len() >= self.max {
+            return false;
+        }
+        times.push_back(now);
+        true
+    }
+}
+
+#[async_trait]
+impl<H> Handler for GroupRateLimit<H>
+where
+    H: Handler + Send,
+{
+    async fn handle(&self, event: QEvent) {
+        if let Some(group_code) = event.group_code() {
+            if !self.acquire(group_code).await {
+                tracing::debug!(
+                    "group {} rate limited, {} dropped",
+                    group_code,
+                    event.kind()
+                );
+                return;
+            }
+        }
+        self.inner.handle(event).await
+    }
+}
+
+/// 捕获 Handler 中的 panic 并打印日志，避免影响后续事件
+pub struct CatchPanic<H> {
+    inner: H,
+}
+
+impl<H> CatchPanic<H> {
+    pub fn new(inner: H) -> Self {
+        Self { inner }
+    }
+}
+
+fn panic_message(err: &(dyn Any + Send)) -> &str {
+    if let Some(s) = err.downcast_ref::<&str>() {
+        s
+    } else if let Some(s) = err.downcast_ref::<String>() {
+        s
+    } else {
+        "unknown panic"
+    }
+}
+
+#[async_trait]
+impl<H> Handler for CatchPanic<H>
+where
+    H: Handler + Send,
+{
+    async fn handle(&self, event: QEvent) {
+        let kind = event.kind();
+        if let Err(err) = AssertUnwindSafe(self.inner.handle(event))
+            .catch_unwind()
+            .await
+        {
+            tracing::error!("handler panicked on {}: {}", kind, panic_message(&*err));
+        }
+    }
+}
+
+/// 组合 Handler 中间件
+///
+/// 后添加的中间件包在外层，先执行。
+///
+/// ```
+/// # use std::time::Duration;
+/// # use ricq::handler::{HandlerBuilder, QEvent};
+/// async fn on_event(e: QEvent) {
+///     tracing::info!("{:?}", e);
+/// }
+/// let handler = HandlerBuilder::new(on_event as fn(_) -> _)
+///     .kinds(["group_message", "friend_message"])
+///     .groups([123456])
+///     .rate_limit_per_group(20, Duration::from_secs(60))
+///     .dedup(Duration::from_secs(60))
+///     .catch_panic()
+///     .build();
+/// ```
+pub struct HandlerBuilder {
+    inner: BoxedHandler,
+}
+
+impl HandlerBuilder {
+    pub fn new<H>(handler: H) -> Self
+    where
+        H: Handler + Send + Sync + 'static,
+    {
+        Self {
+            inner: Box::new(handler),
+        }
+    }
+
+    fn wrap<H>(self, f: impl FnOnce(BoxedHandler) -> H) -> Self
+    where
+        H: Handler + Send + Sync + 'static,
+    {
+        Self::new(f(self.inner))
+    }
+
+    /// 只处理满足条件的事件
+    pub fn filter<F>(self, predicate: F) -> Self
+    where
+        F: Fn(&QEvent) -> bool + Send + Sync + 'static,
+    {
+        self.wrap(|h| Filter::new(h, predicate))
+    }
+
+    /// 群事件只处理指定的群，非群事件不受影响
+    pub fn groups(self, groups: impl IntoIterator<Item = i64>) -> Self {
+        let groups: HashSet<i64> = groups.into_iter().collect();
+        self.filter(move |e| e.group_code().is_none_or(|g| groups.contains(&g)))
+    }
+
+    /// 忽略指定用户触发的事件
+    pub fn ignore_uins(self, uins: impl IntoIterator<Item = i64>) -> Self {
+        let uins: HashSet<i64> = uins.into_iter().collect();
+        self.filter(move |e| e.uin().is_none_or(|u| !uins.contains(&u)))
+    }
+
+    /// 只处理指定用户触发的事件，与用户无关的事件不受影响
+    pub fn uins(self, uins: impl IntoIterator<Item = i64>) -> Self {
+        let uins: HashSet<i64> = uins.into_iter().collect();
+        self.filter(move |e| e.uin().is_none_or(|u| uins.contains(&u)))
+    }
+
+    /// 只处理指定类型的事件，名称见 [`QEvent::kind`]
+    pub fn kinds(self, kinds: impl IntoIterator<Item = &'static str>) -> Self {
+        let kinds: HashSet<&'static str> = kinds.into_iter().collect();
+        self.filter(move |e| kinds.contains(e.kind()))
+    }
+
+    /// 当前 Handler 处理完后再交给 next
+    pub fn chain<H>(self, next: H) -> Self
+    where
+        H: Handler + Send + Sync + 'static,
+    {
+        self.wrap(|h| Chain::new(h, next))
+    }
+
+    /// 同时交给当前 Handler 和 others 处理
+    pub fn fan_out(self, others: Vec<BoxedHandler>) -> Self {
+        self.wrap(|h| {
+            let mut handlers = vec![h];
+            handlers.extend(others);
+            FanOut::new(handlers)
+        })
+    }
+
+    /// 丢弃 lifespan 内重复的消息，lifespan 按整秒计算，最短 1 秒
+    pub fn dedup(self, lifespan: Duration) -> Self {
+        self.wrap(|h| Dedup::new(h, lifespan))
+    }
+
+    /// 每个群在 period 内最多处理 max 个事件
+    pub fn rate_limit_per_group(self, max: usize, period: Duration) -> Self {
+        self.wrap(|h| GroupRateLimit::new(h, max, period))
+    }
+
+    /// 捕获 panic 并打印日志
+    pub fn catch_panic(self) -> Self {
+        self.wrap(CatchPanic::new)
+    }
+
+    pub fn build(self) -> BoxedHandler {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use ricq_core::structs::{FriendMessage, GroupMessage};
+
+    use super::*;
+    use crate::client::event::{FriendMessageEvent, GroupMessageEvent};
+    use crate::client::stream::tests::new_client;
+    use crate::Client;
+
+    #[derive(Default)]
+    struct Counter(AtomicUsize);
+
+    #[async_trait]
+    impl Handler for Counter {
+        async fn handle(&self, _event: QEvent) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    struct Panic;
+
+    #[async_trait]
+    impl Handler for Panic {
+        async fn handle(&self, _event: QEvent) {
+            panic!("boom");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_builder() {
+        let a = Arc::new(Counter::default());
+        let b = Arc::new(Counter::default());
+        let handler = HandlerBuilder::new(a.clone())
+            .fan_out(vec![Box::new(b.clone())])
+            .filter(|e| matches!(e, QEvent::Login(uin) if *uin != 0))
+            .build();
+        handler.handle(QEvent::Login(1)).await;
+        handler.handle(QEvent::Login(0)).await;
+        assert_eq!(a.0.load(Ordering::SeqCst), 1);
+        assert_eq!(b.0.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_catch_panic() {
+        let counter = Arc::new(Counter::default());
+        let handler = HandlerBuilder::new(Panic)
+            .catch_panic()
+            .chain(counter.clone())
+            .kinds(["login"])
+            .build();
+        handler.handle(QEvent::Login(1)).await;
+        assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    }
+
+    fn group_message(client: &Arc<Client>, group_code: i64, seq: i32) -> QEvent {
+        QEvent::GroupMessage(GroupMessageEvent {
+            client: client.clone(),
+            inner: GroupMessage {
+                group_code,
+                seqs: vec![seq],
+                ..Default::default()
+            },
+        })
+    }
+
+    fn friend_message(client: &Arc<Client>, from_uin: i64, target: i64, seq: i32) -> QEvent {
+        QEvent::FriendMessage(FriendMessageEvent {
+            client: client.clone(),
+            inner: FriendMessage {
+                from_uin,
+                target,
+                seqs: vec![seq],
+                ..Default::default()
+            },
+        })
+    }
+
+    #[tokio::test]
+    async fn test_dedup() {
+        let client = Arc::new(new_client());
+        let counter = Arc::new(Counter::default());
+        let dedup = Dedup::new(counter.clone(), Duration::from_secs(1));
+        dedup.handle(group_message(&client, 1, 1)).await;
+        dedup.handle(group_message(&client, 1, 1)).await;
+        dedup.handle(group_message(&client, 2, 1)).await;
+        // 其他设备发给不同好友的消息
+        dedup.handle(friend_message(&client, 100, 200, 1)).await;
+        dedup.handle(friend_message(&client, 100, 300, 1)).await;
+        dedup.handle(friend_message(&client, 100, 300, 1)).await;
+        dedup.handle(QEvent::Login(1)).await;
+        dedup.handle(QEvent::Login(1)).await;
+        assert_eq!(counter.0.load(Ordering::SeqCst), 6);
+
+        // 过期的 key 在 miss 超过 1000 次后被清理
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        for seq in 2..1100 {
+            dedup.handle(group_message(&client, 1, seq)).await;
+        }
+        assert!(dedup.seen.lock().await.cache_size() < 1100);
+    }
+
+    #[tokio::test]
+    async fn test_group_rate_limit() {
+        let client = Arc::new(new_client());
+        let counter = Arc::new(Counter::default());
+        let limit = GroupRateLimit::new(counter.clone(), 2, Duration::from_secs(60));
+        for seq in 0..3 {
+            limit.handle(group_message(&client, 1, seq)).await;
+        }
+        limit.handle(group_message(&client, 2, 0)).await;
+        limit.handle(QEvent::Login(1)).await;
+        assert_eq!(counter.0.load(Ordering::SeqCst), 4);
+    }
+}
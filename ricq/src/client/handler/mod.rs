@@ -10,6 +10,12 @@ use tokio::sync::{
 
 use crate::client::event::*;
 
+pub use middleware::{
+    BoxedHandler, CatchPanic, Chain, Dedup, FanOut, Filter, GroupRateLimit, HandlerBuilder,
+};
+
+mod middleware;
+
 /// 所有需要外发的数据的枚举打包
 #[derive(Clone, derivative::Derivative)]
 #[derivative(Debug)]
@@ -48,14 +54,30 @@ pub enum QEvent {
     GroupDisband(GroupDisbandEvent),
     /// 好友戳一戳
     FriendPoke(FriendPokeEvent),
+    /// 其他客户端（PC、平板等）上线/下线
+    OtherClientStatus(OtherClientStatusEvent),
+    /// 好友输入状态（正在输入）
+    FriendInputStatus(FriendInputStatusEvent),
+    /// 好友资料变更（昵称、头像、签名、备注）
+    FriendProfileUpdate(FriendProfileUpdateEvent),
     /// 群成员戳一戳
     GroupPoke(GroupPokeEvent),
+    /// 群精华消息设置/移除
+    GroupEssenceChange(GroupEssenceChangeEvent),
+    /// 群荣誉变更（龙王等）
+    GroupHonorChange(GroupHonorChangeEvent),
+    /// 群文件上传
+    GroupFileUpload(GroupFileUploadEvent),
     /// 群名称修改
     GroupNameUpdate(GroupNameUpdateEvent),
     /// 好友删除
     DeleteFriend(DeleteFriendEvent),
     /// 群成员权限变更
     MemberPermissionChange(MemberPermissionChangeEvent),
+    /// 群名片修改
+    MemberCardChange(MemberCardChangeEvent),
+    /// 群头衔修改
+    MemberSpecialTitleChange(MemberSpecialTitleChangeEvent),
     /// 被其他客户端踢下线
     /// 不能用于掉线重连，掉线重连以 start 返回为准
     KickedOffline(KickedOfflineEvent),
@@ -65,6 +87,108 @@ pub enum QEvent {
     /// 网络原因/客户端主动掉线
     /// 可用于掉线重连
     ClientDisconnect(ClientDisconnect),
+    /// 未处理的推送，需要在 Config 中开启 report_unhandled
+    Unhandled(UnhandledEvent),
+}
+
+impl QEvent {
+    /// 事件名称，与 PartlyHandler 的方法名对应，如 `group_message`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            QEvent::Login(_) => "login",
+            QEvent::GroupMessage(_) => "group_message",
+            QEvent::GroupAudioMessage(_) => "group_audio_message",
+            QEvent::FriendMessage(_) => "friend_message",
+            QEvent::FriendAudioMessage(_) => "friend_audio_message",
+            QEvent::GroupTempMessage(_) => "group_temp_message",
+            QEvent::GroupRequest(_) => "group_request",
+            QEvent::SelfInvited(_) => "self_invited",
+            QEvent::NewFriendRequest(_) => "new_friend_request",
+            QEvent::NewMember(_) => "new_member",
+            QEvent::GroupMute(_) => "group_mute",
+            QEvent::FriendMessageRecall(_) => "friend_message_recall",
+            QEvent::GroupMessageRecall(_) => "group_message_recall",
+            QEvent::NewFriend(_) => "new_friend",
+            QEvent::GroupLeave(_) => "group_leave",
+            QEvent::GroupDisband(_) => "group_disband",
+            QEvent::FriendPoke(_) => "friend_poke",
+            QEvent::OtherClientStatus(_) => "other_client_status",
+            QEvent::FriendInputStatus(_) => "friend_input_status",
+            QEvent::FriendProfileUpdate(_) => "friend_profile_update",
+            QEvent::GroupPoke(_) => "group_poke",
+            QEvent::GroupEssenceChange(_) => "group_essence_change",
+            QEvent::GroupHonorChange(_) => "group_honor_change",
+            QEvent::GroupFileUpload(_) => "group_file_upload",
+            QEvent::GroupNameUpdate(_) => "group_name_update",
+            QEvent::DeleteFriend(_) => "delete_friend",
+            QEvent::MemberPermissionChange(_) => "member_permission_change",
+            QEvent::MemberCardChange(_) => "member_card_change",
+            QEvent::MemberSpecialTitleChange(_) => "member_special_title_change",
+            QEvent::KickedOffline(_) => "kicked_offline",
+            QEvent::MSFOffline(_) => "msf_offline",
+            QEvent::ClientDisconnect(_) => "client_disconnect",
+            QEvent::Unhandled(_) => "unhandled",
+        }
+    }
+
+    /// 群相关事件的群号
+    pub fn group_code(&self) -> Option<i64> {
+        match self {
+            QEvent::GroupMessage(e) => Some(e.inner.group_code),
+            QEvent::GroupAudioMessage(e) => Some(e.inner.group_code),
+            QEvent::GroupTempMessage(e) => Some(e.inner.group_code),
+            QEvent::GroupRequest(e) => Some(e.inner.group_code),
+            QEvent::SelfInvited(e) => Some(e.inner.group_code),
+            QEvent::NewMember(e) => Some(e.inner.group_code),
+            QEvent::GroupMute(e) => Some(e.inner.group_code),
+            QEvent::GroupMessageRecall(e) => Some(e.inner.group_code),
+            QEvent::GroupLeave(e) => Some(e.inner.group_code),
+            QEvent::GroupDisband(e) => Some(e.inner.group_code),
+            QEvent::GroupPoke(e) => Some(e.inner.group_code),
+            QEvent::GroupEssenceChange(e) => Some(e.inner.group_code),
+            QEvent::GroupHonorChange(e) => Some(e.inner.group_code),
+            QEvent::GroupFileUpload(e) => Some(e.inner.group_code),
+            QEvent::GroupNameUpdate(e) => Some(e.inner.group_code),
+            QEvent::MemberPermissionChange(e) => Some(e.inner.group_code),
+            QEvent::MemberCardChange(e) => Some(e.inner.group_code),
+            QEvent::MemberSpecialTitleChange(e) => Some(e.inner.group_code),
+            _ => None,
+        }
+    }
+
+    /// 触发事件的用户：消息发送者、申请人、操作者或变更的成员
+    pub fn uin(&self) -> Option<i64> {
+        match self {
+            QEvent::GroupMessage(e) => Some(e.inner.from_uin),
+            QEvent::GroupAudioMessage(e) => Some(e.inner.from_uin),
+            QEvent::FriendMessage(e) => Some(e.inner.from_uin),
+            QEvent::FriendAudioMessage(e) => Some(e.inner.from_uin),
+            QEvent::GroupTempMessage(e) => Some(e.inner.from_uin),
+            QEvent::GroupRequest(e) => Some(e.inner.req_uin),
+            QEvent::SelfInvited(e) => Some(e.inner.invitor_uin),
+            QEvent::NewFriendRequest(e) => Some(e.inner.req_uin),
+            QEvent::NewMember(e) => Some(e.inner.member_uin),
+            QEvent::GroupMute(e) => Some(e.inner.operator_uin),
+            QEvent::FriendMessageRecall(e) => Some(e.inner.friend_uin),
+            QEvent::GroupMessageRecall(e) => Some(e.inner.operator_uin),
+            QEvent::NewFriend(e) => Some(e.inner.uin),
+            QEvent::GroupLeave(e) => Some(e.inner.member_uin),
+            QEvent::GroupDisband(e) => Some(e.inner.operator_uin),
+            QEvent::FriendPoke(e) => Some(e.inner.sender),
+            QEvent::FriendInputStatus(e) => Some(e.inner.uin),
+            QEvent::FriendProfileUpdate(e) => Some(e.inner.uin),
+            QEvent::GroupPoke(e) => Some(e.inner.sender),
+            QEvent::GroupEssenceChange(e) => Some(e.inner.operator_uin),
+            QEvent::GroupHonorChange(e) => Some(e.inner.member_uin),
+            QEvent::GroupFileUpload(e) => Some(e.inner.uploader_uin),
+            QEvent::GroupNameUpdate(e) => Some(e.inner.operator_uin),
+            QEvent::DeleteFriend(e) => Some(e.inner.uin),
+            QEvent::MemberPermissionChange(e) => Some(e.inner.member_uin),
+            QEvent::MemberCardChange(e) => Some(e.inner.member_uin),
+            QEvent::MemberSpecialTitleChange(e) => Some(e.inner.member_uin),
+            _ => None,
+        }
+    }
 }
 
 /// 处理外发数据的接口
@@ -83,14 +207,16 @@ pub enum QEvent {
 /// 或者只定义单个事件处理函数，更简洁：
 ///
 /// ```
-/// # use ricq::{Client, Device, Protocol, handler::QEvent};
-/// # fn test(device: Device) {
+/// # use std::sync::Arc;
+/// # use ricq::{Client, Device, Protocol, handler::QEvent, qsign::QSignClient};
+/// # fn test(device: Device, qsign_client: Arc<QSignClient>) {
 /// async fn on_event(e: QEvent) {
 ///     dbg!(e);
 /// }
 /// let client = Client::new(
 ///     device,
 ///     Protocol::MacOS.into(),
+///     qsign_client,
 ///     on_event as fn(_) -> _,
 /// );
 /// # }
@@ -197,13 +323,22 @@ pub trait PartlyHandler: Sync {
     async fn handle_group_leave(&self, _event: GroupLeaveEvent) {}
     async fn handle_group_disband(&self, _event: GroupDisbandEvent) {}
     async fn handle_friend_poke(&self, _event: FriendPokeEvent) {}
+    async fn handle_other_client_status(&self, _event: OtherClientStatusEvent) {}
+    async fn handle_friend_input_status(&self, _event: FriendInputStatusEvent) {}
+    async fn handle_friend_profile_update(&self, _event: FriendProfileUpdateEvent) {}
     async fn handle_group_poke(&self, _event: GroupPokeEvent) {}
+    async fn handle_group_essence_change(&self, _event: GroupEssenceChangeEvent) {}
+    async fn handle_group_honor_change(&self, _event: GroupHonorChangeEvent) {}
+    async fn handle_group_file_upload(&self, _event: GroupFileUploadEvent) {}
     async fn handle_group_name_update(&self, _event: GroupNameUpdateEvent) {}
     async fn handle_delete_friend(&self, _event: DeleteFriendEvent) {}
     async fn handle_member_permission_change(&self, _event: MemberPermissionChangeEvent) {}
+    async fn handle_member_card_change(&self, _event: MemberCardChangeEvent) {}
+    async fn handle_member_special_title_change(&self, _event: MemberSpecialTitleChangeEvent) {}
     async fn handle_kicked_offline(&self, _event: KickedOfflineEvent) {}
     async fn handle_msf_offline(&self, _event: MSFOfflineEvent) {}
     async fn handle_client_disconnect(&self, _event: ClientDisconnect) {}
+    async fn handle_unhandled(&self, _event: UnhandledEvent) {}
 }
 
 #[async_trait]
@@ -230,13 +365,22 @@ where
             QEvent::GroupLeave(m) => self.handle_group_leave(m).await,
             QEvent::GroupDisband(m) => self.handle_group_disband(m).await,
             QEvent::FriendPoke(m) => self.handle_friend_poke(m).await,
+            QEvent::OtherClientStatus(m) => self.handle_other_client_status(m).await,
+            QEvent::FriendInputStatus(m) => self.handle_friend_input_status(m).await,
+            QEvent::FriendProfileUpdate(m) => self.handle_friend_profile_update(m).await,
             QEvent::GroupPoke(m) => self.handle_group_poke(m).await,
+            QEvent::GroupEssenceChange(m) => self.handle_group_essence_change(m).await,
+            QEvent::GroupHonorChange(m) => self.handle_group_honor_change(m).await,
+            QEvent::GroupFileUpload(m) => self.handle_group_file_upload(m).await,
             QEvent::GroupNameUpdate(m) => self.handle_group_name_update(m).await,
             QEvent::DeleteFriend(m) => self.handle_delete_friend(m).await,
             QEvent::MemberPermissionChange(m) => self.handle_member_permission_change(m).await,
+            QEvent::MemberCardChange(m) => self.handle_member_card_change(m).await,
+            QEvent::MemberSpecialTitleChange(m) => self.handle_member_special_title_change(m).await,
             QEvent::KickedOffline(m) => self.handle_kicked_offline(m).await,
             QEvent::MSFOffline(m) => self.handle_msf_offline(m).await,
             QEvent::ClientDisconnect(m) => self.handle_client_disconnect(m).await,
+            QEvent::Unhandled(m) => self.handle_unhandled(m).await,
         }
     }
 }
@@ -21,6 +21,7 @@ use ricq_core::protocol::{device::Device, packet::Packet};
 use ricq_core::structs::{AccountInfo, AddressInfo, OtherClientInfo};
 use ricq_core::Engine;
 pub use ricq_core::Token;
+pub use stream::{EventLagged, EventStream};
 
 use crate::qsign::{QSignClient, QSignResponse, RequestCallback, SignData};
 use crate::{RQError, RQResult};
@@ -32,7 +33,9 @@ mod highway;
 pub(crate) mod net;
 mod processor;
 pub mod qimei;
+mod stream;
 mod tcp;
+mod waiter;
 
 const SIGN_COMMANDS: &str = r#"ConnAuthSvr.fast_qq_login
 ConnAuthSvr.sdk_auth_api
@@ -125,6 +128,8 @@ wtlogin_device.tran_sim_emp"#;
 pub struct Client {
     /// QEvent Handler 调用 handle 方法外发 QEvent
     handler: Box<dyn handler::Handler + Sync + Send + 'static>,
+    /// 事件订阅 Sender
+    event_sender: broadcast::Sender<handler::QEvent>,
     pub engine: RwLock<Engine>,
 
     // 状态相关
@@ -136,6 +141,10 @@ pub struct Client {
     pub online: AtomicBool,
     /// 心跳包是否已启用
     pub heartbeat_enabled: AtomicBool,
+    /// 消息超出单条大小上限时的处理方式
+    pub long_message_policy: RwLock<crate::LongMessagePolicy>,
+    /// 是否上报未处理的推送
+    pub report_unhandled: AtomicBool,
 
     // 包相关
     /// 外发包 Sender
@@ -144,6 +153,8 @@ pub struct Client {
     packet_promises: RwLock<HashMap<i32, oneshot::Sender<Packet>>>,
     /// 当前客户端发送消息后使用 cache 避免上报自身消息事件
     receipt_waiters: Mutex<cached::TimedCache<i32, oneshot::Sender<i32>>>,
+    /// wait_for 等待中的消息
+    message_waiters: Mutex<Vec<waiter::MessageWaiter>>,
 
     // account info
     pub account_info: RwLock<AccountInfo>,
@@ -160,6 +171,12 @@ pub struct Client {
 
     /// 群消息 builder 寄存 <div_seq, parts> : parts is sorted by pkg_index
     group_message_builder: RwLock<cached::TimedCache<i32, Vec<GroupMessagePart>>>,
+    /// 每个群最后收到的消息 seq，用于断线重连后补齐消息
+    group_last_seqs: RwLock<HashMap<i64, i32>>,
+    /// 已收到的群消息 <(group_code, seq)>，避免补齐的消息重复上报
+    group_seq_cache: RwLock<cached::TimedCache<(i64, i32), ()>>,
+    /// 群成员名片 <(group_code, uin), card>，用于群名片修改事件
+    member_card_cache: RwLock<cached::TimedCache<(i64, i64), String>>,
     /// 每个 28 Byte
     c2c_cache: RwLock<cached::TimedCache<(i64, i64, i32, i64), ()>>,
     push_req_cache: RwLock<cached::TimedCache<(i16, i64), ()>>,
@@ -188,24 +205,35 @@ impl super::Client {
     {
         let (out_pkt_sender, _) = tokio::sync::broadcast::channel(1024);
         let (disconnect_signal, _) = tokio::sync::broadcast::channel(8);
+        let (event_sender, _) = tokio::sync::broadcast::channel(stream::EVENT_CHANNEL_CAPACITY);
 
         Client {
-            handler: Box::new(handler),
+            handler: Box::new(stream::Broadcast {
+                inner: handler,
+                sender: event_sender.clone(),
+            }),
+            event_sender,
             engine: RwLock::new(Engine::new(device, version)),
             status: AtomicU8::new(NetworkStatus::Unknown as u8),
             heartbeat_enabled: AtomicBool::new(false),
             online: AtomicBool::new(false),
+            long_message_policy: Default::default(),
+            report_unhandled: AtomicBool::new(false),
             out_pkt_sender,
             disconnect_signal,
             // out_going_packet_session_id: RwLock::new(Bytes::from_static(&[0x02, 0xb0, 0x5b, 0x8b])),
             packet_promises: Default::default(),
             receipt_waiters: Mutex::new(cached::TimedCache::with_lifespan(60)),
+            message_waiters: Default::default(),
             account_info: Default::default(),
             address: Default::default(),
             online_clients: Default::default(),
             last_message_time: Default::default(),
             start_time: UNIX_EPOCH.elapsed().unwrap().as_secs() as i32,
             group_message_builder: RwLock::new(cached::TimedCache::with_lifespan(600)),
+            group_last_seqs: Default::default(),
+            group_seq_cache: RwLock::new(cached::TimedCache::with_lifespan(3600)),
+            member_card_cache: RwLock::new(cached::TimedCache::with_lifespan(86400)),
             c2c_cache: RwLock::new(cached::TimedCache::with_lifespan(3600)),
             push_req_cache: RwLock::new(cached::TimedCache::with_lifespan(30)),
             push_trans_cache: RwLock::new(cached::TimedCache::with_lifespan(15)),
@@ -228,7 +256,10 @@ impl super::Client {
     where
         H: crate::client::handler::Handler + 'static + Sync + Send,
     {
-        Self::new(config.device, config.version, qsign_client, handler)
+        let mut client = Self::new(config.device, config.version, qsign_client, handler);
+        *client.long_message_policy.get_mut() = config.long_message_policy;
+        *client.report_unhandled.get_mut() = config.report_unhandled;
+        client
     }
 
     /// 获取当前 Client uin
@@ -249,7 +280,7 @@ impl super::Client {
                 &pkt.command_name,
                 pkt.seq_id,
                 &pkt.body,
-                &engine
+                engine
                     .transport
                     .device
                     .qimei
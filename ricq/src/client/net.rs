@@ -141,8 +141,10 @@ impl crate::Client {
                     }
                 }
                 output = rx.recv() => {
-                    if let Ok(output) = output && write_half.send(output).await.is_err() {
-                        break;
+                    if let Ok(output) = output {
+                        if write_half.send(output).await.is_err() {
+                            break;
+                        }
                     }
                 }
                 _ = disconnect_signal.recv() => {
@@ -13,22 +13,26 @@ impl Client {
     pub(crate) async fn process_friend_message(
         self: &Arc<Self>,
         mut msg: pb::msg::Message,
+        offline: bool,
     ) -> RQResult<()> {
         fn take_ptt(msg: &mut pb::msg::Message) -> Option<pb::msg::Ptt> {
             msg.body.as_mut()?.rich_text.as_mut()?.ptt.take()
         }
         if let Some(ptt) = take_ptt(&mut msg) {
             // TODO self friend audio
+            let mut message = parse_friend_audio_message(msg, ptt)?;
+            message.offline = offline;
             self.handler
                 .handle(QEvent::FriendAudioMessage(FriendAudioMessageEvent {
                     client: self.clone(),
-                    inner: parse_friend_audio_message(msg, ptt)?,
+                    inner: message,
                 }))
                 .await;
             return Ok(());
         }
 
-        let message = parse_friend_message(msg)?;
+        let mut message = parse_friend_message(msg)?;
+        message.offline = offline;
         if message.from_uin == self.uin().await {
             if let Some(tx) = self
                 .receipt_waiters
@@ -39,13 +43,13 @@ impl Client {
                 let _ = tx.send(message.seqs.first().cloned().unwrap_or_default());
                 return Ok(());
             }
+            message.from_self_device = true;
         }
-        self.handler
-            .handle(QEvent::FriendMessage(FriendMessageEvent {
-                client: self.clone(),
-                inner: message,
-            }))
-            .await;
+        self.dispatch_message(QEvent::FriendMessage(FriendMessageEvent {
+            client: self.clone(),
+            inner: message,
+        }))
+        .await;
         Ok(())
     }
 }
@@ -66,6 +70,8 @@ pub fn parse_friend_message(msg: pb::msg::Message) -> RQResult<FriendMessage> {
             },
         ],
         elements: MessageChain::from(msg.body.unwrap().rich_text.unwrap().elems), // todo ptt_store
+        offline: false,
+        from_self_device: false,
     })
 }
 
@@ -88,5 +94,6 @@ pub fn parse_friend_audio_message(
             },
         ],
         audio: FriendAudio(ptt),
+        offline: false,
     })
 }
@@ -12,14 +12,15 @@ impl Client {
     pub(crate) async fn process_temp_message(
         self: &Arc<Self>,
         msg: pb::msg::Message,
+        offline: bool,
     ) -> RQResult<()> {
-        let message = parse_temp_message(msg)?;
-        self.handler
-            .handle(QEvent::GroupTempMessage(GroupTempMessageEvent {
-                client: self.clone(),
-                inner: message,
-            }))
-            .await;
+        let mut message = parse_temp_message(msg)?;
+        message.offline = offline;
+        self.dispatch_message(QEvent::GroupTempMessage(GroupTempMessageEvent {
+            client: self.clone(),
+            inner: message,
+        }))
+        .await;
         Ok(())
     }
 }
@@ -44,5 +45,6 @@ pub fn parse_temp_message(msg: pb::msg::Message) -> RQResult<GroupTempMessage> {
         from_nick: head.from_nick.unwrap_or_default(),
         elements: MessageChain::from(msg.body.unwrap().rich_text.unwrap().elems), // todo ptt_store
         group_code: tmp_head.group_code.unwrap_or_default(),
+        offline: false,
     })
 }
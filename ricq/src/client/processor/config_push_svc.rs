@@ -51,7 +51,7 @@ impl Client {
                             // 去重，排序
                             {
                                 let mut addrs = self.highway_addrs.read().await.clone();
-                                addrs.dedup_by(|a, b| (a.0 == b.0 && a.1 == b.1));
+                                addrs.dedup_by(|a, b| a.0 == b.0 && a.1 == b.1);
                                 let sorted_addrs = sort_addrs(addrs, Duration::from_secs(5)).await;
                                 let mut highway_addrs = self.highway_addrs.write().await;
                                 highway_addrs.clear();
@@ -2,7 +2,9 @@ use std::sync::Arc;
 use std::time::UNIX_EPOCH;
 
 use cached::Cached;
+use prost::Message;
 
+use ricq_core::structs::UnhandledPush;
 use ricq_core::{jce, pb};
 
 use crate::client::event::KickedOfflineEvent;
@@ -42,7 +44,7 @@ impl Client {
         let all_message = self.sync_all_message().await;
         match all_message {
             Ok(msgs) => {
-                self.process_message_sync(msgs).await;
+                self.process_message_sync(msgs, false).await;
             }
             Err(err) => {
                 tracing::warn!("failed to sync message {}", err);
@@ -63,15 +65,20 @@ impl Client {
             .await;
     }
 
-    pub(crate) async fn process_message_sync(self: &Arc<Self>, msgs: Vec<pb::msg::Message>) {
+    /// offline: 登录时拉取的离线消息，不过滤登录前的消息
+    pub(crate) async fn process_message_sync(
+        self: &Arc<Self>,
+        msgs: Vec<pb::msg::Message>,
+        offline: bool,
+    ) {
         for msg in msgs {
             let head = msg.head.clone().unwrap();
-            if self.msg_exists(&head).await {
+            if self.msg_exists(&head, offline).await {
                 continue;
             }
             match msg.head.as_ref().unwrap().msg_type() {
                 9 | 10 | 31 | 79 | 97 | 120 | 132 | 133 | 166 | 167 => {
-                    if let Err(err) = self.process_friend_message(msg).await {
+                    if let Err(err) = self.process_friend_message(msg, offline).await {
                         tracing::error!("failed to process friend message {err}");
                     }
                 }
@@ -80,23 +87,37 @@ impl Client {
                         tracing::error!("failed to process join group {err}");
                     }
                 }
+                82 if offline => {
+                    if let Err(err) = self.process_offline_group_message(msg).await {
+                        tracing::error!("failed to process offline group message {err}");
+                    }
+                }
                 140 | 141 => {
-                    if let Err(err) = self.process_temp_message(msg).await {
+                    if let Err(err) = self.process_temp_message(msg, offline).await {
                         tracing::error!("failed to process temp message {err}");
                     }
                 }
                 208 => {
                     // friend ptt_store
                 }
-                _ => tracing::warn!("unhandled sync message type"),
+                msg_type => {
+                    tracing::warn!("unhandled sync message type");
+                    self.report_unhandled(UnhandledPush {
+                        command: "MessageSvc.PbGetMsg".into(),
+                        msg_type: Some(msg_type as i64),
+                        sub_type: None,
+                        body: msg.encode_to_vec().into(),
+                    })
+                    .await;
+                }
             }
         }
     }
 
-    async fn msg_exists(&self, head: &pb::msg::MessageHead) -> bool {
+    async fn msg_exists(&self, head: &pb::msg::MessageHead, offline: bool) -> bool {
         let now = UNIX_EPOCH.elapsed().unwrap().as_secs() as i32;
         let msg_time = head.msg_time.unwrap_or_default();
-        if now - msg_time > 60 || self.start_time > msg_time {
+        if !offline && (now - msg_time > 60 || self.start_time > msg_time) {
             return true;
         }
         let mut c2c_cache = self.c2c_cache.write().await;
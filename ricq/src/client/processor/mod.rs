@@ -1,8 +1,13 @@
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use bytes::Bytes;
 
 use ricq_core::protocol::packet::Packet;
+use ricq_core::structs::UnhandledPush;
+
+use crate::client::event::UnhandledEvent;
+use crate::client::handler::QEvent;
 
 pub mod c2c;
 pub mod config_push_svc;
@@ -199,8 +204,27 @@ impl super::Client {
                 }
                 _ => {
                     tracing::debug!("unhandled pkt: {}", &pkt.command_name);
+                    cli.report_unhandled(UnhandledPush {
+                        command: pkt.command_name.clone(),
+                        body: pkt.body,
+                        ..Default::default()
+                    })
+                    .await;
                 }
             }
         });
     }
+
+    /// 上报未处理的推送，未开启 report_unhandled 时忽略
+    pub(crate) async fn report_unhandled(self: &Arc<Self>, push: UnhandledPush) {
+        if !self.report_unhandled.load(Ordering::Relaxed) {
+            return;
+        }
+        self.handler
+            .handle(QEvent::Unhandled(UnhandledEvent {
+                client: self.clone(),
+                inner: push,
+            }))
+            .await;
+    }
 }
@@ -6,18 +6,25 @@ use cached::Cached;
 
 use prost::Message;
 use ricq_core::command::online_push::GroupMessagePart;
-use ricq_core::command::online_push::{OnlinePushTrans, PushTransInfo};
-use ricq_core::msg::MessageChain;
+use ricq_core::command::online_push::{GrayTip, OnlinePushTrans, PushTransInfo};
+use ricq_core::msg::elem::GroupFile;
+use ricq_core::msg::{MessageChain, MessageElem};
 use ricq_core::structs::{
-    DeleteFriend, FriendInfo, FriendMessageRecall, FriendPoke, GroupAudio, GroupAudioMessage,
-    GroupLeave, GroupMessage, GroupMessageRecall, GroupMute, GroupNameUpdate, GroupPoke,
+    DeleteFriend, FriendInfo, FriendInputStatus, FriendMessageRecall, FriendPoke,
+    FriendProfileUpdate, GroupAudio, GroupAudioMessage, GroupEssenceChange, GroupFileUpload,
+    GroupHonor, GroupHonorChange, GroupLeave, GroupMessage, GroupMessageRecall, GroupMute,
+    GroupNameUpdate, GroupPoke, MemberCardChange, MemberSpecialTitleChange, ProfileChange,
+    UnhandledPush,
 };
 use ricq_core::{jce, pb};
 
 use crate::client::event::{
-    DeleteFriendEvent, FriendMessageRecallEvent, FriendPokeEvent, GroupAudioMessageEvent,
-    GroupDisbandEvent, GroupLeaveEvent, GroupMessageEvent, GroupMessageRecallEvent, GroupMuteEvent,
-    GroupNameUpdateEvent, GroupPokeEvent, MemberPermissionChangeEvent, NewFriendEvent,
+    DeleteFriendEvent, FriendInputStatusEvent, FriendMessageRecallEvent, FriendPokeEvent,
+    FriendProfileUpdateEvent, GroupAudioMessageEvent, GroupDisbandEvent, GroupEssenceChangeEvent,
+    GroupFileUploadEvent, GroupHonorChangeEvent, GroupLeaveEvent, GroupMessageEvent,
+    GroupMessageRecallEvent, GroupMuteEvent, GroupNameUpdateEvent, GroupPokeEvent,
+    MemberCardChangeEvent, MemberPermissionChangeEvent, MemberSpecialTitleChangeEvent,
+    NewFriendEvent,
 };
 use crate::client::handler::QEvent;
 use crate::client::Client;
@@ -28,6 +35,13 @@ impl Client {
         self: &Arc<Self>,
         group_message_part: GroupMessagePart,
     ) -> RQResult<()> {
+        if !self
+            .mark_group_seq(group_message_part.group_code, group_message_part.seq)
+            .await
+        {
+            return Ok(());
+        }
+
         // receipt message
         if group_message_part.from_uin == self.uin().await {
             if let Some(tx) = self
@@ -41,6 +55,21 @@ impl Client {
             }
         }
 
+        // 匿名消息的名片是匿名昵称
+        let anonymous = group_message_part
+            .elems
+            .iter()
+            .any(|e| matches!(e.elem, Some(pb::msg::elem::Elem::AnonGroupMsg(_))));
+        if !anonymous {
+            self.update_member_card(
+                group_message_part.group_code,
+                group_message_part.from_uin,
+                group_message_part.group_card.clone(),
+                true,
+            )
+            .await;
+        }
+
         if let Some(ptt) = group_message_part.ptt {
             self.handler
                 .handle(QEvent::GroupAudioMessage(GroupAudioMessageEvent {
@@ -86,12 +115,166 @@ impl Client {
         // handle message
         if let Some(group_msg) = group_msg {
             // message is finish
-            self.handler
-                .handle(QEvent::GroupMessage(GroupMessageEvent {
-                    client: self.clone(),
-                    inner: self.parse_group_message(group_msg).await?,
-                }))
-                .await; //todo
+            let group_msg = self.parse_group_message(group_msg).await?;
+            let file_upload = group_msg
+                .elements
+                .0
+                .iter()
+                .find_map(|e| match e {
+                    MessageElem::TransElemInfo(e) => GroupFile::from_trans_elem(e),
+                    _ => None,
+                })
+                .map(|file| GroupFileUpload {
+                    group_code: group_msg.group_code,
+                    uploader_uin: group_msg.from_uin,
+                    time: group_msg.time,
+                    file,
+                });
+            self.dispatch_group_message(group_msg).await;
+            if let Some(file_upload) = file_upload {
+                self.handler
+                    .handle(QEvent::GroupFileUpload(GroupFileUploadEvent {
+                        client: self.clone(),
+                        inner: file_upload,
+                    }))
+                    .await;
+            }
+        }
+        Ok(())
+    }
+
+    /// 登录时拉取到的离线群消息
+    pub(crate) async fn process_offline_group_message(
+        self: &Arc<Self>,
+        msg: pb::msg::Message,
+    ) -> RQResult<()> {
+        let part = GroupMessagePart::try_from(msg)?;
+        if !self.mark_group_seq(part.group_code, part.seq).await {
+            return Ok(());
+        }
+        let mut message = self.parse_group_message(vec![part]).await?;
+        message.offline = true;
+        self.dispatch_group_message(message).await;
+        Ok(())
+    }
+
+    /// 本账号发送的消息标记为 from_self_device，本客户端发送的消息只用于回执
+    async fn dispatch_group_message(self: &Arc<Self>, mut group_msg: GroupMessage) {
+        if group_msg.from_uin == self.uin().await {
+            let rand = group_msg.rands.first().cloned().unwrap_or_default();
+            if let Some(tx) = self.receipt_waiters.lock().await.cache_remove(&rand) {
+                let _ = tx.send(group_msg.seqs.first().cloned().unwrap_or_default());
+                return;
+            }
+            group_msg.from_self_device = true;
+        }
+        self.dispatch_message(QEvent::GroupMessage(GroupMessageEvent {
+            client: self.clone(),
+            inner: group_msg,
+        }))
+        .await;
+    }
+
+    /// 更新缓存的群名片，名片变化时上报
+    ///
+    /// known_only: 之前的名片未知时不上报
+    async fn update_member_card(
+        self: &Arc<Self>,
+        group_code: i64,
+        member_uin: i64,
+        card: String,
+        known_only: bool,
+    ) {
+        let mut cache = self.member_card_cache.write().await;
+        // cache_set 不计入 miss，先 cache_get 以便按 miss 数清理过期的名片
+        let old_card = cache.cache_get(&(group_code, member_uin)).cloned();
+        cache.cache_set((group_code, member_uin), card.clone());
+        if cache.cache_misses().unwrap_or_default() > 100 {
+            cache.flush();
+            cache.cache_reset_metrics();
+        }
+        drop(cache);
+        if old_card.as_ref() == Some(&card) || (known_only && old_card.is_none()) {
+            return;
+        }
+        self.handler
+            .handle(QEvent::MemberCardChange(MemberCardChangeEvent {
+                client: self.clone(),
+                inner: MemberCardChange {
+                    group_code,
+                    member_uin,
+                    old_card,
+                    new_card: card,
+                },
+            }))
+            .await;
+    }
+
+    /// 记录收到的群消息 seq，已经收到过时返回 false
+    async fn mark_group_seq(&self, group_code: i64, seq: i32) -> bool {
+        let mut cache = self.group_seq_cache.write().await;
+        if cache.cache_get(&(group_code, seq)).is_some() {
+            return false;
+        }
+        cache.cache_set((group_code, seq), ());
+        if cache.cache_misses().unwrap_or_default() > 100 {
+            cache.flush();
+            cache.cache_reset_metrics();
+        }
+        let mut last_seqs = self.group_last_seqs.write().await;
+        let last_seq = last_seqs.entry(group_code).or_insert(seq);
+        *last_seq = seq.max(*last_seq);
+        true
+    }
+
+    /// 补齐断线期间漏掉的群消息，以 `recovered` 的群消息事件上报
+    ///
+    /// 只补齐断线前收到过消息的群，每个群最多补齐最近 100 条，在重新登录后调用
+    pub async fn recover_group_messages(self: &Arc<Self>) -> RQResult<()> {
+        let last_seqs = self.group_last_seqs.read().await.clone();
+        let group_codes: Vec<i64> = last_seqs.keys().copied().collect();
+        for group_codes in group_codes.chunks(50) {
+            let groups = match self.get_group_infos(group_codes.to_vec()).await {
+                Ok(groups) => groups,
+                Err(err) => {
+                    tracing::warn!("failed to get group infos for recovery: {}", err);
+                    continue;
+                }
+            };
+            for group in groups {
+                let Some(&last_seq) = last_seqs.get(&group.code) else {
+                    continue;
+                };
+                let end_seq = group.last_msg_seq as i32;
+                if end_seq <= last_seq {
+                    continue;
+                }
+                let begin_seq = (last_seq + 1).max(end_seq - 99);
+                let messages = match self
+                    .get_group_message_history(group.code, begin_seq, end_seq)
+                    .await
+                {
+                    Ok(messages) => messages,
+                    Err(err) => {
+                        tracing::warn!("failed to recover group {} messages: {}", group.code, err);
+                        continue;
+                    }
+                };
+                for mut message in messages {
+                    let mut new = false;
+                    for &seq in &message.seqs {
+                        new |= self.mark_group_seq(group.code, seq).await;
+                    }
+                    if !new {
+                        continue;
+                    }
+                    if message.group_name.is_empty() {
+                        message.group_name = group.name.clone();
+                    }
+                    message.recovered = true;
+                    self.dispatch_group_message(message).await;
+                }
+            }
         }
         Ok(())
     }
@@ -100,7 +283,7 @@ impl Client {
         &self,
         mut parts: Vec<GroupMessagePart>,
     ) -> RQResult<GroupMessage> {
-        parts.sort_by(|a, b| a.pkg_index.cmp(&b.pkg_index));
+        parts.sort_by_key(|p| p.pkg_index);
 
         let group_code = parts.first().map(|p| p.group_code).unwrap_or_default();
         let group_name = parts
@@ -133,6 +316,9 @@ impl Client {
             from_uin,
             time,
             elements: MessageChain(elements),
+            recovered: false,
+            offline: false,
+            from_self_device: false,
         })
 
         // TODO: extInfo
@@ -147,7 +333,7 @@ impl Client {
             }
             match info.msg_type {
                 732 => {
-                    let mut r = info.v_msg;
+                    let mut r = info.v_msg.clone();
                     let group_code = r.get_u32() as i64;
                     let i_type = r.get_u8();
                     r.get_u8();
@@ -200,7 +386,92 @@ impl Client {
                                 }
                             }
 
+                            if let Some(t) = b.opt_msg_gray_tips {
+                                let tip = GrayTip::parse(&String::from_utf8_lossy(&t.content));
+                                if tip.text.contains("头衔") {
+                                    // 解析不出 uin 时不上报
+                                    let member_uin = tip
+                                        .segment(5)
+                                        .and_then(|s| s.data.parse::<i64>().ok())
+                                        .filter(|&uin| uin != 0);
+                                    if let (Some(member_uin), Some(title)) =
+                                        (member_uin, tip.segment(1))
+                                    {
+                                        self.handler
+                                            .handle(QEvent::MemberSpecialTitleChange(
+                                                MemberSpecialTitleChangeEvent {
+                                                    client: self.clone(),
+                                                    inner: MemberSpecialTitleChange {
+                                                        group_code,
+                                                        member_uin,
+                                                        new_title: title.text.clone(),
+                                                    },
+                                                },
+                                            ))
+                                            .await;
+                                    }
+                                }
+                            }
+
+                            if let Some(digest) = b.qq_group_digest_msg {
+                                self.handler
+                                    .handle(QEvent::GroupEssenceChange(GroupEssenceChangeEvent {
+                                        client: self.clone(),
+                                        inner: GroupEssenceChange {
+                                            group_code,
+                                            msg_seq: digest.seq as i32,
+                                            msg_rand: digest.random as i32,
+                                            // 1: 设为精华 2: 移出精华
+                                            set: digest.op_type == 1,
+                                            sender_uin: digest.sender as i64,
+                                            sender_nick: String::from_utf8_lossy(
+                                                &digest.sender_nick,
+                                            )
+                                            .into_owned(),
+                                            operator_uin: digest.digest_oper as i64,
+                                            operator_nick: String::from_utf8_lossy(
+                                                &digest.oper_nick,
+                                            )
+                                            .into_owned(),
+                                            time: digest.op_time as i32,
+                                        },
+                                    }))
+                                    .await;
+                            }
+
                             if let Some(t) = b.opt_general_gray_tip {
+                                let honor = match t.templ_id {
+                                    1052 => Some(GroupHonor::Performer),
+                                    // 获得龙王/蝉联龙王
+                                    1053 | 1054 => Some(GroupHonor::Talkative),
+                                    1067 => Some(GroupHonor::Emotion),
+                                    _ => None,
+                                };
+                                if let Some(honor) = honor {
+                                    let mut member_uin: i64 = 0;
+                                    let mut member_nick = String::new();
+                                    for templ in &t.msg_templ_param {
+                                        match &*templ.name {
+                                            "uin" => {
+                                                member_uin = templ.value.parse().unwrap_or_default()
+                                            }
+                                            "nick" => member_nick = templ.value.clone(),
+                                            _ => {}
+                                        }
+                                    }
+                                    self.handler
+                                        .handle(QEvent::GroupHonorChange(GroupHonorChangeEvent {
+                                            client: self.clone(),
+                                            inner: GroupHonorChange {
+                                                group_code,
+                                                member_uin,
+                                                member_nick,
+                                                honor,
+                                            },
+                                        }))
+                                        .await;
+                                }
+                                // 戳一戳，荣誉提示中没有 uin_str1
                                 let mut sender: i64 = 0;
                                 let mut receiver: i64 = 0;
                                 for templ in t.msg_templ_param {
@@ -229,7 +500,15 @@ impl Client {
                             }
                             // TODO 一些没什么用的 event 暂时没写
                         }
-                        _ => {}
+                        _ => {
+                            self.report_unhandled(UnhandledPush {
+                                command: "OnlinePush.ReqPush".into(),
+                                msg_type: Some(info.msg_type as i64),
+                                sub_type: Some(i_type as i64),
+                                body: info.v_msg,
+                            })
+                            .await;
+                        }
                     }
                 }
                 528 => {
@@ -300,6 +579,28 @@ impl Client {
                                     .await;
                             }
                         }
+                        0x115 => {
+                            let s115 =
+                                match pb::msgtype0x210::SubMsg0x115Body::decode(&*msg.v_protobuf) {
+                                    Ok(s115) => s115,
+                                    Err(err) => {
+                                        tracing::warn!("failed to decode 0x115 push: {}", err);
+                                        continue;
+                                    }
+                                };
+                            if let Some(item) = s115.msg_notify_item {
+                                self.handler
+                                    .handle(QEvent::FriendInputStatus(FriendInputStatusEvent {
+                                        client: self.clone(),
+                                        inner: FriendInputStatus {
+                                            uin: s115.from_uin.unwrap_or_default() as i64,
+                                            // 1: 正在输入 2: 停止输入
+                                            inputting: item.event_type.unwrap_or_default() == 1,
+                                        },
+                                    }))
+                                    .await;
+                            }
+                        }
                         0x27 => {
                             let s27 =
                                 pb::msgtype0x210::SubMsg0x27Body::decode(&*msg.v_protobuf).unwrap();
@@ -330,6 +631,68 @@ impl Client {
                                             .await;
                                     }
                                 }
+                                if let Some(profile) = mod_info.mod_group_member_profile {
+                                    for profile_info in &profile.group_member_profile_infos {
+                                        // 1: 群名片
+                                        if profile_info.field() != 1 {
+                                            continue;
+                                        }
+                                        self.update_member_card(
+                                            profile.group_code() as i64,
+                                            profile.uin() as i64,
+                                            String::from_utf8_lossy(profile_info.value())
+                                                .into_owned(),
+                                            false,
+                                        )
+                                        .await;
+                                    }
+                                }
+                                if let Some(profile) = mod_info.mod_profile {
+                                    for profile_info in &profile.profile_infos {
+                                        // 20002: 昵称
+                                        if profile_info.field() != 20002 {
+                                            continue;
+                                        }
+                                        self.process_profile_update(
+                                            profile.uin() as i64,
+                                            ProfileChange::Nickname(
+                                                String::from_utf8_lossy(profile_info.value())
+                                                    .into_owned(),
+                                            ),
+                                        )
+                                        .await;
+                                    }
+                                }
+                                if let Some(face) = mod_info.mod_custom_face {
+                                    // 有 group_code 的是群头像
+                                    if face.group_code() == 0 {
+                                        self.process_profile_update(
+                                            face.uin() as i64,
+                                            ProfileChange::Avatar,
+                                        )
+                                        .await;
+                                    }
+                                }
+                                if let Some(long_nick) = mod_info.mod_long_nick {
+                                    self.process_profile_update(
+                                        long_nick.uin() as i64,
+                                        ProfileChange::Signature(
+                                            String::from_utf8_lossy(long_nick.value()).into_owned(),
+                                        ),
+                                    )
+                                    .await;
+                                }
+                                if let Some(remark) = mod_info.mod_friend_remark {
+                                    for r in remark.frd_rmk {
+                                        self.process_profile_update(
+                                            r.fuin() as i64,
+                                            ProfileChange::Remark(
+                                                String::from_utf8_lossy(r.rmk_name()).into_owned(),
+                                            ),
+                                        )
+                                        .await;
+                                    }
+                                }
                                 if let Some(del_friend) = mod_info.del_friend {
                                     for uin in del_friend.uins {
                                         self.handler
@@ -346,12 +709,42 @@ impl Client {
                             // group sync
                             // friend sync
                         }
-                        _ => {}
+                        _ => {
+                            self.report_unhandled(UnhandledPush {
+                                command: "OnlinePush.ReqPush".into(),
+                                msg_type: Some(info.msg_type as i64),
+                                sub_type: Some(msg.sub_msg_type),
+                                body: msg.v_protobuf,
+                            })
+                            .await;
+                        }
                     }
                 }
-                _ => {}
+                _ => {
+                    self.report_unhandled(UnhandledPush {
+                        command: "OnlinePush.ReqPush".into(),
+                        msg_type: Some(info.msg_type as i64),
+                        sub_type: None,
+                        body: info.v_msg,
+                    })
+                    .await;
+                }
+            }
+        }
+    }
+
+    async fn process_profile_update(self: &Arc<Self>, uin: i64, change: ProfileChange) {
+        if let ProfileChange::Nickname(nickname) = &change {
+            if uin == self.uin().await {
+                self.account_info.write().await.nickname = nickname.clone();
             }
         }
+        self.handler
+            .handle(QEvent::FriendProfileUpdate(FriendProfileUpdateEvent {
+                client: self.clone(),
+                inner: FriendProfileUpdate { uin, change },
+            }))
+            .await;
     }
 
     async fn push_req_exists(&self, info: &jce::PushMessageInfo) -> bool {
@@ -438,7 +831,7 @@ impl Client {
         );
         let _ = self.send(req).await?;
         if let Some(msg) = push.msg {
-            self.process_message_sync(vec![msg]).await;
+            self.process_message_sync(vec![msg], false).await;
         }
         Ok(())
     }
@@ -1,15 +1,45 @@
+use std::sync::Arc;
+
+use ricq_core::structs::OtherClientStatus;
+
+use crate::client::event::OtherClientStatusEvent;
+use crate::client::handler::QEvent;
 use crate::client::{Client, OtherClientInfo};
 use crate::RQError;
 
-// use crate::client::income::decoder::online_push::GroupMessagePart;
-
 impl Client {
     pub(crate) async fn process_push_param(
-        &self,
+        self: &Arc<Self>,
         other_clients: Vec<OtherClientInfo>,
     ) -> Result<(), RQError> {
         tracing::debug!("{:?}", other_clients);
-        // TODO merge part and dispatch to handler
+        // PushParam 每次推送完整的在线列表，与上次比较得到上线/下线
+        let old_clients = std::mem::replace(
+            &mut *self.online_clients.write().await,
+            other_clients.clone(),
+        );
+        let mut changes = Vec::new();
+        for info in &old_clients {
+            if !other_clients.contains(info) {
+                changes.push(OtherClientStatus {
+                    info: info.clone(),
+                    online: false,
+                });
+            }
+        }
+        for info in other_clients {
+            if !old_clients.contains(&info) {
+                changes.push(OtherClientStatus { info, online: true });
+            }
+        }
+        for inner in changes {
+            self.handler
+                .handle(QEvent::OtherClientStatus(OtherClientStatusEvent {
+                    client: self.clone(),
+                    inner,
+                }))
+                .await;
+        }
         Ok(())
     }
 }
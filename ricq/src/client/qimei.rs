@@ -20,7 +20,7 @@ pub async fn get_qimei<RNG: RngCore + CryptoRng>(
         .json()
         .await
         .map_err(|e| RQError::Other(e.to_string()))?;
-    resp.to_payload(crypt_key).map_err(Into::into)
+    resp.to_payload(crypt_key)
 }
 
 #[cfg(test)]
@@ -0,0 +1,149 @@
+use std::fmt;
+
+use async_trait::async_trait;
+use futures_util::future::ready;
+use futures_util::stream::{self, BoxStream};
+use futures_util::StreamExt;
+use tokio::sync::broadcast;
+
+use crate::client::event::{FriendMessageEvent, GroupMessageEvent, GroupTempMessageEvent};
+use crate::client::handler::{Handler, QEvent};
+use crate::Client;
+
+/// 订阅的事件缓冲数量，超出后落后的订阅者会收到 [`EventLagged`]
+pub(crate) const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// 订阅者处理太慢，跳过了若干事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventLagged(pub u64);
+
+impl fmt::Display for EventLagged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "event subscriber lagged, {} events skipped", self.0)
+    }
+}
+
+impl std::error::Error for EventLagged {}
+
+pub type EventStream<T> = BoxStream<'static, Result<T, EventLagged>>;
+
+/// 先广播给订阅者，再交给用户的 Handler
+pub(crate) struct Broadcast<H> {
+    pub(crate) inner: H,
+    pub(crate) sender: broadcast::Sender<QEvent>,
+}
+
+#[async_trait]
+impl<H> Handler for Broadcast<H>
+where
+    H: Handler + Send,
+{
+    async fn handle(&self, event: QEvent) {
+        if self.sender.receiver_count() > 0 {
+            let _ = self.sender.send(event.clone());
+        }
+        self.inner.handle(event).await
+    }
+}
+
+impl Client {
+    /// 订阅之后的所有事件
+    ///
+    /// 与 Handler 互不影响，可以随时订阅多次，drop 即取消订阅。
+    pub fn subscribe(&self) -> EventStream<QEvent> {
+        stream::unfold(self.event_sender.subscribe(), |mut rx| async move {
+            match rx.recv().await {
+                Ok(event) => Some((Ok(event), rx)),
+                Err(broadcast::error::RecvError::Lagged(n)) => Some((Err(EventLagged(n)), rx)),
+                Err(broadcast::error::RecvError::Closed) => None,
+            }
+        })
+        .boxed()
+    }
+
+    /// 订阅指定类型的事件
+    pub fn subscribe_map<T, F>(&self, f: F) -> EventStream<T>
+    where
+        T: Send + 'static,
+        F: Fn(QEvent) -> Option<T> + Send + 'static,
+    {
+        self.subscribe()
+            .filter_map(move |r| ready(r.map(&f).transpose()))
+            .boxed()
+    }
+
+    /// 订阅群消息
+    pub fn group_messages(&self) -> EventStream<GroupMessageEvent> {
+        self.subscribe_map(|e| match e {
+            QEvent::GroupMessage(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    /// 订阅好友消息
+    pub fn friend_messages(&self) -> EventStream<FriendMessageEvent> {
+        self.subscribe_map(|e| match e {
+            QEvent::FriendMessage(e) => Some(e),
+            _ => None,
+        })
+    }
+
+    /// 订阅群临时消息
+    pub fn group_temp_messages(&self) -> EventStream<GroupTempMessageEvent> {
+        self.subscribe_map(|e| match e {
+            QEvent::GroupTempMessage(e) => Some(e),
+            _ => None,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::*;
+    use crate::qsign::QSignClient;
+    use crate::{Device, Protocol};
+
+    async fn ignore(_: QEvent) {}
+
+    pub(crate) fn new_client() -> Client {
+        let qsign_client = QSignClient::new(
+            "http://localhost:8080".into(),
+            "114514".into(),
+            Duration::from_secs(60),
+        )
+        .unwrap();
+        Client::new(
+            Device::random(),
+            Protocol::IPad.into(),
+            Arc::new(qsign_client),
+            ignore as fn(_) -> _,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_subscribe() {
+        let client = new_client();
+        let mut all = client.subscribe();
+        let mut logins = client.subscribe_map(|e| match e {
+            QEvent::Login(uin) => Some(uin),
+            _ => None,
+        });
+        client.handler.handle(QEvent::Login(1)).await;
+        assert!(matches!(all.next().await, Some(Ok(QEvent::Login(1)))));
+        assert_eq!(logins.next().await, Some(Ok(1)));
+    }
+
+    #[tokio::test]
+    async fn test_lagged() {
+        let client = new_client();
+        let mut all = client.subscribe();
+        for uin in 0..EVENT_CHANNEL_CAPACITY as i64 + 10 {
+            client.handler.handle(QEvent::Login(uin)).await;
+        }
+        assert_eq!(all.next().await.unwrap().unwrap_err(), EventLagged(10));
+        assert!(matches!(all.next().await, Some(Ok(QEvent::Login(10)))));
+    }
+}
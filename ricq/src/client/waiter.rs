@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use ricq_core::structs::{FriendMessage, GroupMessage};
+
+use crate::client::event::{FriendMessageEvent, GroupMessageEvent};
+use crate::client::handler::QEvent;
+use crate::{Client, RQError, RQResult};
+
+pub(crate) struct MessageWaiter {
+    predicate: Box<dyn Fn(&QEvent) -> bool + Send + Sync>,
+    sender: oneshot::Sender<QEvent>,
+}
+
+impl Client {
+    /// 等待下一个满足条件的消息事件，超时返回 [`RQError::Timeout`]
+    ///
+    /// 被等待者接收的消息不会再交给 Handler，但仍会广播给 [`Client::subscribe`] 的订阅者。
+    /// 仅群消息、好友消息、群临时消息会被匹配，断线补齐和离线消息不参与匹配。
+    pub async fn wait_for<F>(&self, predicate: F, timeout: Duration) -> RQResult<QEvent>
+    where
+        F: Fn(&QEvent) -> bool + Send + Sync + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        self.message_waiters.lock().await.push(MessageWaiter {
+            predicate: Box::new(predicate),
+            sender,
+        });
+        // 超时后 receiver drop，waiter 会在下一条消息到达时清理
+        match tokio::time::timeout(timeout, receiver).await {
+            Ok(Ok(event)) => Ok(event),
+            _ => Err(RQError::Timeout),
+        }
+    }
+
+    /// 等待下一条满足条件的群消息
+    pub async fn wait_for_group_message<F>(
+        &self,
+        predicate: F,
+        timeout: Duration,
+    ) -> RQResult<GroupMessageEvent>
+    where
+        F: Fn(&GroupMessage) -> bool + Send + Sync + 'static,
+    {
+        let event = self
+            .wait_for(
+                move |e| matches!(e, QEvent::GroupMessage(m) if predicate(&m.inner)),
+                timeout,
+            )
+            .await?;
+        match event {
+            QEvent::GroupMessage(m) => Ok(m),
+            _ => unreachable!(),
+        }
+    }
+
+    /// 等待下一条满足条件的好友消息
+    pub async fn wait_for_friend_message<F>(
+        &self,
+        predicate: F,
+        timeout: Duration,
+    ) -> RQResult<FriendMessageEvent>
+    where
+        F: Fn(&FriendMessage) -> bool + Send + Sync + 'static,
+    {
+        let event = self
+            .wait_for(
+                move |e| matches!(e, QEvent::FriendMessage(m) if predicate(&m.inner)),
+                timeout,
+            )
+            .await?;
+        match event {
+            QEvent::FriendMessage(m) => Ok(m),
+            _ => unreachable!(),
+        }
+    }
+
+    /// 优先交给 wait_for 的等待者，没有匹配时交给 Handler
+    pub(crate) async fn dispatch_message(&self, event: QEvent) {
+        if let Some(event) = self.resolve_waiter(event).await {
+            self.handler.handle(event).await;
+        }
+    }
+
+    async fn resolve_waiter(&self, event: QEvent) -> Option<QEvent> {
+        if is_replayed(&event) {
+            return Some(event);
+        }
+        let mut waiters = self.message_waiters.lock().await;
+        waiters.retain(|w| !w.sender.is_closed());
+        let i = match waiters.iter().position(|w| (w.predicate)(&event)) {
+            Some(i) => i,
+            None => return Some(event),
+        };
+        // 被接收的消息不经过 Handler 中的 Broadcast，需要单独广播给订阅者
+        let copy = (self.event_sender.receiver_count() > 0).then(|| event.clone());
+        match waiters.remove(i).sender.send(event) {
+            Ok(()) => {
+                if let Some(copy) = copy {
+                    let _ = self.event_sender.send(copy);
+                }
+                None
+            }
+            Err(event) => Some(event),
+        }
+    }
+}
+
+/// 断线补齐或离线期间的消息
+fn is_replayed(event: &QEvent) -> bool {
+    match event {
+        QEvent::GroupMessage(e) => e.inner.recovered || e.inner.offline,
+        QEvent::FriendMessage(e) => e.inner.offline,
+        QEvent::GroupTempMessage(e) => e.inner.offline,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures_util::StreamExt;
+
+    use super::*;
+    use crate::client::stream::tests::new_client;
+
+    #[tokio::test]
+    async fn test_wait_for() {
+        let client = new_client();
+        let wait = client.wait_for(|e| matches!(e, QEvent::Login(2)), Duration::from_secs(1));
+        let resolve = async {
+            // 等待 waiter 注册
+            tokio::task::yield_now().await;
+            assert!(client.resolve_waiter(QEvent::Login(1)).await.is_some());
+            assert!(client.resolve_waiter(QEvent::Login(2)).await.is_none());
+        };
+        let (event, _) = tokio::join!(wait, resolve);
+        assert!(matches!(event, Ok(QEvent::Login(2))));
+        assert!(client.message_waiters.lock().await.is_empty());
+
+        let event = client.wait_for(|_| true, Duration::from_millis(10)).await;
+        assert!(matches!(event, Err(RQError::Timeout)));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_broadcast() {
+        let client = Arc::new(new_client());
+        let mut all = client.subscribe();
+        let wait = client.wait_for(|_| true, Duration::from_secs(1));
+        let resolve = async {
+            tokio::task::yield_now().await;
+            let recovered = QEvent::GroupMessage(GroupMessageEvent {
+                client: client.clone(),
+                inner: GroupMessage {
+                    recovered: true,
+                    ..Default::default()
+                },
+            });
+            assert!(client.resolve_waiter(recovered).await.is_some());
+            assert!(client.resolve_waiter(QEvent::Login(1)).await.is_none());
+        };
+        let (event, _) = tokio::join!(wait, resolve);
+        assert!(matches!(event, Ok(QEvent::Login(1))));
+        assert!(matches!(all.next().await, Some(Ok(QEvent::Login(1)))));
+    }
+}
@@ -10,6 +10,22 @@ use ricq_core::protocol::{
 pub struct Config {
     pub device: Device,
     pub version: Version,
+    /// 消息超出单条大小上限时的处理方式，默认直接发送
+    pub long_message_policy: LongMessagePolicy,
+    /// 是否上报未处理的推送 (QEvent::Unhandled)，默认关闭
+    pub report_unhandled: bool,
+}
+
+/// 消息超出单条大小上限时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LongMessagePolicy {
+    /// 直接发送，由服务器决定是否接收
+    #[default]
+    Disabled,
+    /// 拆分为多条消息发送，接收方自动合并
+    Fragment,
+    /// 上传后以长消息卡片发送
+    LongMessage,
 }
 
 impl Default for Config {
@@ -17,12 +33,19 @@ impl Default for Config {
         Self {
             device: Device::random(),
             version: get_version(Protocol::IPad),
+            long_message_policy: Default::default(),
+            report_unhandled: false,
         }
     }
 }
 
 impl Config {
     pub fn new(device: Device, version: Version) -> Self {
-        Self { device, version }
+        Self {
+            device,
+            version,
+            long_message_policy: Default::default(),
+            report_unhandled: false,
+        }
     }
 }
@@ -0,0 +1,596 @@
+//! 消息命令路由
+//!
+//! ```
+//! # use ricq::ext::command::{Command, CommandRouter};
+//! # use ricq::structs::GroupMemberPermission;
+//! # use ricq::msg::MessageChain;
+//! # use ricq::msg::elem::Text;
+//! let router = CommandRouter::new()
+//!     .help("help")
+//!     .command(
+//!         Command::new("echo").alias("复读").description("复读参数"),
+//!         |ctx| async move {
+//!             let text = ctx.text_args().join(" ");
+//!             ctx.reply(MessageChain::new(Text::new(text))).await?;
+//!             Ok(())
+//!         },
+//!     )
+//!     .command(
+//!         Command::new("mute")
+//!             .usage("<@成员> <分钟>")
+//!             .permission(GroupMemberPermission::Administrator)
+//!             .group_only(),
+//!         |_ctx| async move { Ok(()) },
+//!     );
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+
+use async_trait::async_trait;
+use cached::{Cached, TimedCache};
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use tokio::sync::Mutex;
+
+use ricq_core::msg::elem::{At, FlashImage, FriendImage, GroupImage, RQElem, Text};
+use ricq_core::msg::MessageChain;
+use ricq_core::structs::{GroupMemberPermission, MessageReceipt};
+
+use crate::client::event::{FriendMessageEvent, GroupMessageEvent};
+use crate::handler::{Handler, QEvent};
+use crate::{Client, RQResult};
+
+/// 命令参数
+#[derive(Debug, Clone)]
+pub enum CommandArg {
+    Text(String),
+    At(At),
+    GroupImage(GroupImage),
+    FriendImage(FriendImage),
+    FlashImage(FlashImage),
+    /// 表情等其他元素
+    Other(RQElem),
+}
+
+impl CommandArg {
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            CommandArg::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_at(&self) -> Option<&At> {
+        match self {
+            CommandArg::At(at) => Some(at),
+            _ => None,
+        }
+    }
+}
+
+/// 按空白拆分文本，双引号内的空白保留
+fn split_text(text: &str, args: &mut Vec<CommandArg>) {
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut has_token = false;
+    for c in text.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                has_token = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if has_token {
+                    args.push(CommandArg::Text(std::mem::take(&mut current)));
+                    has_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_token = true;
+            }
+        }
+    }
+    if has_token {
+        args.push(CommandArg::Text(current));
+    }
+}
+
+/// 把消息拆分为参数
+///
+/// 相邻的文本先合并再按空白拆分，At 和图片作为单独的参数，回复引用会被忽略。
+pub fn parse_args(chain: MessageChain) -> Vec<CommandArg> {
+    let mut args = Vec::new();
+    let mut text = String::new();
+    for elem in chain {
+        let arg = match elem {
+            RQElem::Text(t) => {
+                text.push_str(&t.content);
+                continue;
+            }
+            RQElem::At(at) => CommandArg::At(at),
+            RQElem::GroupImage(i) => CommandArg::GroupImage(i),
+            RQElem::FriendImage(i) => CommandArg::FriendImage(i),
+            RQElem::FlashImage(i) => CommandArg::FlashImage(i),
+            RQElem::Other(_) => continue,
+            e => CommandArg::Other(e),
+        };
+        // 元素之间视为分隔
+        split_text(&std::mem::take(&mut text), &mut args);
+        args.push(arg);
+    }
+    split_text(&text, &mut args);
+    args
+}
+
+/// 触发命令的消息
+#[derive(Debug, Clone)]
+pub enum CommandEvent {
+    Group(GroupMessageEvent),
+    Friend(FriendMessageEvent),
+}
+
+impl CommandEvent {
+    fn client(&self) -> &Client {
+        match self {
+            CommandEvent::Group(e) => &e.client,
+            CommandEvent::Friend(e) => &e.client,
+        }
+    }
+
+    fn sender(&self) -> i64 {
+        match self {
+            CommandEvent::Group(e) => e.inner.from_uin,
+            CommandEvent::Friend(e) => e.inner.from_uin,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandContext {
+    pub event: CommandEvent,
+    /// 命令名称（不含前缀，别名会转换为名称）
+    pub name: String,
+    /// 命令名之后的参数
+    pub args: Vec<CommandArg>,
+}
+
+impl CommandContext {
+    pub fn sender(&self) -> i64 {
+        self.event.sender()
+    }
+
+    pub fn group_code(&self) -> Option<i64> {
+        match &self.event {
+            CommandEvent::Group(e) => Some(e.inner.group_code),
+            CommandEvent::Friend(_) => None,
+        }
+    }
+
+    /// 所有文本参数
+    pub fn text_args(&self) -> Vec<&str> {
+        self.args.iter().filter_map(CommandArg::as_text).collect()
+    }
+
+    /// 所有 At 参数
+    pub fn at_args(&self) -> Vec<&At> {
+        self.args.iter().filter_map(CommandArg::as_at).collect()
+    }
+
+    /// 回复到消息来源的群或好友
+    pub async fn reply(&self, message: MessageChain) -> RQResult<MessageReceipt> {
+        match &self.event {
+            CommandEvent::Group(e) => {
+                e.client
+                    .send_group_message(e.inner.group_code, message)
+                    .await
+            }
+            CommandEvent::Friend(e) => {
+                e.client
+                    .send_friend_message(e.inner.from_uin, message)
+                    .await
+            }
+        }
+    }
+}
+
+/// 命令可用的消息来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CommandScope {
+    All,
+    Group,
+    Friend,
+}
+
+/// 命令定义
+#[derive(Debug, Clone)]
+pub struct Command {
+    name: String,
+    aliases: Vec<String>,
+    description: String,
+    usage: String,
+    permission: GroupMemberPermission,
+    scope: CommandScope,
+}
+
+impl Command {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            aliases: Vec::new(),
+            description: String::new(),
+            usage: String::new(),
+            permission: GroupMemberPermission::Member,
+            scope: CommandScope::All,
+        }
+    }
+
+    pub fn alias<S: Into<String>>(mut self, alias: S) -> Self {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    /// 帮助中显示的说明
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// 帮助中显示的参数格式
+    pub fn usage<S: Into<String>>(mut self, usage: S) -> Self {
+        self.usage = usage.into();
+        self
+    }
+
+    /// 群内需要的最低权限，不为 Member 时只有超级用户可以在好友消息中使用
+    pub fn permission(mut self, permission: GroupMemberPermission) -> Self {
+        self.permission = permission;
+        self
+    }
+
+    pub fn group_only(mut self) -> Self {
+        self.scope = CommandScope::Group;
+        self
+    }
+
+    pub fn friend_only(mut self) -> Self {
+        self.scope = CommandScope::Friend;
+        self
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.iter().any(|a| a == name)
+    }
+}
+
+type CommandHandler = Box<dyn Fn(CommandContext) -> BoxFuture<'static, RQResult<()>> + Send + Sync>;
+
+/// 命令路由，实现了 [`Handler`]，只处理群消息和好友消息
+pub struct CommandRouter {
+    prefixes: Vec<String>,
+    superusers: HashSet<i64>,
+    help: Option<String>,
+    commands: Vec<(Command, CommandHandler)>,
+    /// 群成员权限缓存，group_code -> (uin -> 权限)
+    permissions: Mutex<TimedCache<i64, HashMap<i64, GroupMemberPermission>>>,
+    /// 最近获取成员列表失败的群，期间只查询单个成员
+    failed_groups: Mutex<TimedCache<i64, ()>>,
+}
+
+impl Default for CommandRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandRouter {
+    /// 默认前缀为 `/`
+    pub fn new() -> Self {
+        Self {
+            prefixes: vec!["/".into()],
+            superusers: HashSet::new(),
+            help: None,
+            commands: Vec::new(),
+            permissions: Mutex::new(TimedCache::with_lifespan(600)),
+            failed_groups: Mutex::new(TimedCache::with_lifespan(60)),
+        }
+    }
+
+    /// 命令前缀，可以为空字符串
+    pub fn prefixes<S: Into<String>>(mut self, prefixes: impl IntoIterator<Item = S>) -> Self {
+        self.prefixes = prefixes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 超级用户不受权限限制
+    pub fn superusers(mut self, uins: impl IntoIterator<Item = i64>) -> Self {
+        self.superusers = uins.into_iter().collect();
+        self
+    }
+
+    /// 使用指定名称的内置帮助命令
+    pub fn help<S: Into<String>>(mut self, name: S) -> Self {
+        self.help = Some(name.into());
+        self
+    }
+
+    pub fn command<F, Fut>(mut self, command: Command, handler: F) -> Self
+    where
+        F: Fn(CommandContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = RQResult<()>> + Send + 'static,
+    {
+        self.commands
+            .push((command, Box::new(move |ctx| handler(ctx).boxed())));
+        self
+    }
+
+    /// 生成帮助文本
+    pub fn help_text(&self) -> String {
+        let prefix = self
+            .prefixes
+            .first()
+            .map(String::as_str)
+            .unwrap_or_default();
+        let mut lines = Vec::new();
+        for (command, _) in &self.commands {
+            let mut line = format!("{prefix}{}", command.name);
+            if !command.usage.is_empty() {
+                line.push(' ');
+                line.push_str(&command.usage);
+            }
+            if !command.aliases.is_empty() {
+                line.push_str(&format!(" ({})", command.aliases.join(", ")));
+            }
+            if !command.description.is_empty() {
+                line.push_str(" - ");
+                line.push_str(&command.description);
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+
+    /// 解析命令名和参数，第一个参数必须是 前缀+命令名
+    ///
+    /// 开头 At 机器人或被回复者的参数会被跳过（回复时 QQ 会自动插入被回复者的 At）
+    fn parse(&self, chain: MessageChain, self_uin: i64) -> Option<(String, Vec<CommandArg>)> {
+        let quoted = chain.reply().map(|r| r.sender);
+        let mut args = parse_args(chain);
+        while let Some(CommandArg::At(at)) = args.first() {
+            if at.target != self_uin && Some(at.target) != quoted {
+                break;
+            }
+            args.remove(0);
+        }
+        if args.is_empty() {
+            return None;
+        }
+        let first = match args.remove(0) {
+            CommandArg::Text(s) => s,
+            _ => return None,
+        };
+        let name = self
+            .prefixes
+            .iter()
+            .filter_map(|p| first.strip_prefix(p.as_str()))
+            // 最长的前缀优先
+            .min_by_key(|name| name.len())?;
+        if name.is_empty() {
+            return None;
+        }
+        Some((name.to_string(), args))
+    }
+
+    async fn allowed(&self, command: &Command, event: &CommandEvent) -> bool {
+        let sender = event.sender();
+        if self.superusers.contains(&sender) {
+            return true;
+        }
+        if matches!(command.permission, GroupMemberPermission::Member) {
+            return true;
+        }
+        let CommandEvent::Group(e) = event else {
+            return false;
+        };
+        match self
+            .member_permission(&e.client, e.inner.group_code, sender)
+            .await
+        {
+            Ok(permission) => permission.level() >= command.permission.level(),
+            Err(err) => {
+                tracing::warn!("failed to get member permission: {}", err);
+                false
+            }
+        }
+    }
+
+    /// 优先使用缓存的群成员列表，列表中没有时单独查询
+    async fn member_permission(
+        &self,
+        client: &Client,
+        group_code: i64,
+        uin: i64,
+    ) -> RQResult<GroupMemberPermission> {
+        // 获取成员列表时不持有锁，避免阻塞其他群
+        let cached = self
+            .permissions
+            .lock()
+            .await
+            .cache_get(&group_code)
+            .map(|members| members.get(&uin).cloned());
+        match cached {
+            Some(Some(permission)) => return Ok(permission),
+            Some(None) => {}
+            None => {
+                let failed = self
+                    .failed_groups
+                    .lock()
+                    .await
+                    .cache_get(&group_code)
+                    .is_some();
+                if !failed {
+                    match self.fetch_permissions(client, group_code).await {
+                        Ok(members) => {
+                            let permission = members.get(&uin).cloned();
+                            self.permissions.lock().await.cache_set(group_code, members);
+                            if let Some(permission) = permission {
+                                return Ok(permission);
+                            }
+                        }
+                        Err(err) => {
+                            tracing::warn!("failed to get group member list: {}", err);
+                            self.failed_groups.lock().await.cache_set(group_code, ());
+                        }
+                    }
+                }
+            }
+        }
+        let info = client.get_group_member_info(group_code, uin).await?;
+        if let Some(members) = self.permissions.lock().await.cache_get_mut(&group_code) {
+            members.insert(uin, info.permission.clone());
+        }
+        Ok(info.permission)
+    }
+
+    async fn fetch_permissions(
+        &self,
+        client: &Client,
+        group_code: i64,
+    ) -> RQResult<HashMap<i64, GroupMemberPermission>> {
+        let owner_uin = client
+            .get_group_info(group_code)
+            .await?
+            .map(|g| g.owner_uin)
+            .unwrap_or_default();
+        Ok(client
+            .get_group_member_list(group_code, owner_uin)
+            .await?
+            .into_iter()
+            .map(|m| (m.uin, m.permission))
+            .collect())
+    }
+
+    async fn dispatch(&self, event: CommandEvent, chain: MessageChain) {
+        let self_uin = event.client().uin().await;
+        let Some((name, args)) = self.parse(chain, self_uin) else {
+            return;
+        };
+        let scope = match event {
+            CommandEvent::Group(_) => CommandScope::Group,
+            CommandEvent::Friend(_) => CommandScope::Friend,
+        };
+        let Some((command, handler)) = self
+            .commands
+            .iter()
+            .find(|(c, _)| c.matches(&name) && (c.scope == CommandScope::All || c.scope == scope))
+        else {
+            if self.help.as_ref() == Some(&name) {
+                let ctx = CommandContext { event, name, args };
+                if let Err(err) = ctx
+                    .reply(MessageChain::new(Text::new(self.help_text())))
+                    .await
+                {
+                    tracing::warn!("failed to reply help: {}", err);
+                }
+            }
+            return;
+        };
+        if !self.allowed(command, &event).await {
+            tracing::debug!("command {} permission denied", command.name);
+            return;
+        }
+        let ctx = CommandContext {
+            event,
+            name: command.name.clone(),
+            args,
+        };
+        if let Err(err) = handler(ctx).await {
+            tracing::warn!("command {} failed: {}", command.name, err);
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for CommandRouter {
+    async fn handle(&self, event: QEvent) {
+        match event {
+            QEvent::GroupMessage(e) => {
+                let chain = e.inner.elements.clone();
+                self.dispatch(CommandEvent::Group(e), chain).await
+            }
+            QEvent::FriendMessage(e) => {
+                let chain = e.inner.elements.clone();
+                self.dispatch(CommandEvent::Friend(e), chain).await
+            }
+            QEvent::MemberPermissionChange(e) => {
+                if let Some(members) = self
+                    .permissions
+                    .lock()
+                    .await
+                    .cache_get_mut(&e.inner.group_code)
+                {
+                    members.insert(e.inner.member_uin, e.inner.new_permission);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ricq_core::msg::elem::Reply;
+    use ricq_core::msg::MessageElem;
+
+    use super::*;
+
+    fn texts(args: &[CommandArg]) -> Vec<&str> {
+        args.iter().filter_map(CommandArg::as_text).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let mut chain = MessageChain::default();
+        chain.push(Text::new("/mute".into()));
+        chain.push(At::new(12345));
+        chain.push(Text::new(" 10 \"a b\"".into()));
+        chain.push(Text::new("c".into()));
+        let args = parse_args(chain);
+        assert_eq!(args.len(), 4);
+        assert_eq!(args[1].as_at().map(|a| a.target), Some(12345));
+        assert_eq!(texts(&args), vec!["/mute", "10", "a bc"]);
+    }
+
+    #[test]
+    fn test_router() {
+        let router = CommandRouter::new().prefixes(["/", "#"]).command(
+            Command::new("mute").alias("禁言").usage("<@成员> <分钟>"),
+            |_| async { Ok(()) },
+        );
+        let mut chain = MessageChain::default();
+        chain.push(Text::new("#禁言 1".into()));
+        let (name, args) = router.parse(chain, 10000).unwrap();
+        assert_eq!(name, "禁言");
+        assert_eq!(texts(&args), vec!["1"]);
+
+        // 回复时自动插入的 At
+        let mut chain = MessageChain::default();
+        chain.with_reply(Reply {
+            sender: 20000,
+            ..Default::default()
+        });
+        chain.push(At::new(20000));
+        chain.push(Text::new(" /mute".into()));
+        assert_eq!(router.parse(chain.clone(), 10000).unwrap().0, "mute");
+        chain.0.retain(|e| !matches!(e, MessageElem::SrcMsg(_)));
+        assert!(router.parse(chain, 10000).is_none());
+
+        let mut chain = MessageChain::default();
+        chain.push(At::new(10000));
+        chain.push(Text::new("/mute".into()));
+        assert_eq!(router.parse(chain, 10000).unwrap().0, "mute");
+        assert!(router.commands[0].0.matches(&name));
+        assert_eq!(router.help_text(), "/mute <@成员> <分钟> (禁言)");
+    }
+}
@@ -1,3 +1,4 @@
+pub mod command;
 pub mod common;
 pub mod image;
 pub mod login;
@@ -59,6 +59,9 @@ pub async fn auto_reconnect<T: AsyncRead + AsyncWrite + 'static + Send>(
         }
         tracing::info!("succeed to reconnect");
         after_login(&client).await;
+        if let Err(err) = client.recover_group_messages().await {
+            tracing::error!("failed to recover group messages: {}", err);
+        }
         handle.await.ok();
     }
 }
@@ -1,6 +1,3 @@
-#![feature(async_closure)]
-#![feature(let_chains)]
-#![feature(result_flattening)]
 pub mod client;
 mod config;
 pub mod ext;
@@ -9,7 +6,7 @@ pub mod structs;
 
 pub use client::handler;
 pub use client::Client;
-pub use config::Config;
+pub use config::{Config, LongMessagePolicy};
 pub use device::Device;
 pub use version::Protocol;
 